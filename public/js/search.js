@@ -0,0 +1,108 @@
+// Searches /search-index.json, written at build time by src/search.rs.
+// tokens() and stem() must match their Rust counterparts.
+(() => {
+  const input = document.querySelector('#search input');
+  const status = document.getElementById('search-status');
+  const results = document.getElementById('search-results');
+  let index;
+
+  const tokens = (text) =>
+    (text.toLowerCase().match(/[\p{L}\p{N}]+[#+]*/gu) || [])
+      .filter((t) => [...t].length > 1);
+
+  const stem = (word) => {
+    if ([...word].length <= 3 || !/^[a-z]+$/.test(word)) return word;
+
+    if (word.endsWith('sses') || word.endsWith('ies')) {
+      word = word.slice(0, -2);
+      if (word.endsWith('i')) word = word.slice(0, -1) + 'y';
+    } else if (word.endsWith('s') && !/(ss|us|is)$/.test(word)) {
+      word = word.slice(0, -1);
+    }
+
+    for (const suffix of ['ing', 'ed', 'ly']) {
+      const base = word.slice(0, -suffix.length);
+      if (word.endsWith(suffix) && base.length >= 3 && /[aeiouy]/.test(base)) {
+        word = base;
+        const last = word[word.length - 1];
+        if (suffix !== 'ly' && last === word[word.length - 2] && !'aeioulsz'.includes(last)) {
+          word = word.slice(0, -1);
+        }
+        break;
+      }
+    }
+
+    return word;
+  };
+
+  // Every query term has to match. The last one also matches as a prefix
+  // while it's still being typed.
+  const search = (query) => {
+    const words = tokens(query).filter((t) => !index.stop.includes(t));
+    if (words.length === 0) return [];
+
+    let scores = null;
+    words.forEach((word, i) => {
+      const stemmed = stem(word);
+      const matches = {};
+      for (const [term, postings] of Object.entries(index.terms)) {
+        const typing = i === words.length - 1 && !/\s$/.test(query);
+        if (term !== stemmed && !(typing && term.startsWith(word))) continue;
+        for (let p = 0; p < postings.length; p += 2) {
+          matches[postings[p]] = (matches[postings[p]] || 0) + postings[p + 1];
+        }
+      }
+
+      if (scores === null) {
+        scores = matches;
+      } else {
+        for (const doc of Object.keys(scores)) {
+          if (doc in matches) scores[doc] += matches[doc];
+          else delete scores[doc];
+        }
+      }
+    });
+
+    return Object.entries(scores)
+      .sort(([, a], [, b]) => b - a)
+      .map(([doc]) => index.docs[doc]);
+  };
+
+  const render = (query) => {
+    results.replaceChildren();
+    if (!query.trim()) {
+      status.textContent = '';
+      return;
+    }
+
+    const docs = search(query);
+    status.textContent = `${docs.length} post${docs.length === 1 ? '' : 's'} found`;
+    for (const doc of docs) {
+      const item = document.createElement('li');
+      const link = document.createElement('a');
+      link.href = doc.url;
+      link.textContent = doc.title;
+      const details = document.createElement('p');
+      details.className = 'details';
+      details.textContent = `${doc.created} ${doc.tags}`;
+      item.append(link, details);
+      results.append(item);
+    }
+  };
+
+  fetch('/search-index.json')
+    .then((response) => response.json())
+    .then((json) => {
+      index = json;
+      const query = new URLSearchParams(location.search).get('q') || '';
+      input.value = query;
+      render(query);
+
+      input.addEventListener('input', () => {
+        const url = new URL(location);
+        url.searchParams.set('q', input.value);
+        history.replaceState(null, '', url);
+        render(input.value);
+      });
+    });
+})();
@@ -47,6 +47,8 @@ pkgs.mkShell {
   ] ++ (with pkgs; [
     static-web-server   # Static webserver for testing
     wrangler            # Cloudflare CLI
+    gzip                # Precompressed .gz files
+    brotli              # Precompressed .br files
 
     # Rust
     rustc
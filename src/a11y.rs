@@ -0,0 +1,323 @@
+use regex::Regex;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use crate::check::{self, Finding, Sources};
+use crate::html::{self, Tag};
+use crate::io;
+
+// WCAG AA contrast for normal text
+pub const MIN_CONTRAST: f64 = 4.5;
+
+// Lints every generated page under root for common accessibility problems
+// and checks the colour pairs defined in css/main.css
+pub fn lint(root: &Path, sources: &Sources) -> Vec<Finding> {
+  let mut findings = vec![];
+
+  for file in io::files_in_tree(root) {
+    if file.extension().is_some_and(|ext| ext == "html") {
+      let contents = fs::read_to_string(&file).unwrap();
+      for (needle, line, message) in page(&contents) {
+        findings.push(check::locate(root, &file, &needle, line, sources, message));
+      }
+    }
+  }
+
+  let css = root.join("css/main.css");
+  if css.exists() {
+    findings.extend(contrast(&fs::read_to_string(&css).unwrap(), &css));
+  }
+
+  findings
+}
+
+// Problems in a single page as (text to locate it by, line, message)
+pub fn page(contents: &str) -> Vec<(String, usize, String)> {
+  let tags = html::tags(contents);
+  let mut problems = vec![];
+  let mut ids = HashSet::new();
+  let mut heading = 0;
+  let opening = || tags.iter().filter(|t| !t.closing);
+
+  match opening().find(|t| t.name == "html") {
+    Some(tag) if tag.attribute("lang").is_none_or(str::is_empty) => {
+      problems.push(("<html".to_string(), tag.line, "<html> missing lang".to_string()));
+    },
+    _ => (),
+  }
+
+  let title = tags.iter().position(|t| t.name == "title" && !t.closing);
+  let title_text = title.map(|i| {
+    tags[i + 1..].iter().find(|t| t.name == "title").map_or(String::new(), |end| {
+      html::text_between(contents, &tags[i], end)
+    })
+  });
+  if title_text.is_none_or(|t| t.trim().is_empty()) {
+    problems.push(("<head".to_string(), 1, "missing or empty <title>".to_string()));
+  }
+
+  if !opening().any(|t| t.name == "meta" && t.attribute("charset").is_some()) {
+    problems.push(("<head".to_string(), 1, "missing <meta charset>".to_string()));
+  }
+
+  for (i, tag) in tags.iter().enumerate().filter(|(_, t)| !t.closing) {
+    if let Some(id) = tag.attribute("id")
+      && !ids.insert(id)
+    {
+      problems.push((format!("id=\"{id}\""), tag.line, format!("duplicate id {id}")));
+    }
+
+    match tag.name.as_str() {
+      "img" if tag.attribute("alt").is_none() => {
+        let src = tag.attribute("src").unwrap_or_default();
+        problems.push((src.to_string(), tag.line, format!("<img> missing alt text ({src})")));
+      },
+      "a" if tag.attribute("href").is_some() && link_text(contents, &tags, i).is_empty() => {
+        let href = tag.attribute("href").unwrap();
+        let labelled = ["aria-label", "title"].iter().any(|a| {
+          tag.attribute(a).is_some_and(|v| !v.trim().is_empty())
+        });
+        if !labelled {
+          problems.push((href.to_string(), tag.line, format!("empty link text ({href})")));
+        }
+      },
+      name if is_heading(name) => {
+        let level = name[1..].parse::<usize>().unwrap();
+        if heading > 0 && level > heading + 1 {
+          problems.push((
+            format!("<{name}"),
+            tag.line,
+            format!("heading level skipped (h{heading} to {name})"),
+          ));
+        }
+        heading = level;
+      },
+      _ => (),
+    }
+  }
+
+  problems
+}
+
+// A CSS property, its value and line
+type Declaration = (String, String, usize);
+
+// A rule of a stylesheet with its declarations and any nested rules. At-rules
+// like @media hold rules the same way.
+#[derive(Default)]
+struct Rule {
+  selector: String,
+  declarations: Vec<Declaration>,
+  rules: Vec<Rule>,
+}
+
+impl Rule {
+  fn selects(&self, test: impl Fn(&str) -> bool) -> bool {
+    self.selector.split(',').any(|s| test(s.trim()))
+  }
+
+  fn get(&self, property: &str) -> Option<&Declaration> {
+    self.declarations.iter().rev().find(|(p, _, _)| p == property)
+  }
+}
+
+// Contrast between the text colour of each rule and its background, or
+// the nearest enclosing rule's, or the page's. Custom properties resolve in
+// the rule's own scope, and every :root or html override (say in a
+// prefers-color-scheme media query) is checked as a theme of its own.
+pub fn contrast(css: &str, file: &Path) -> Vec<Finding> {
+  let sheet = parse(css);
+  let mut rules = vec![];
+  flatten(&sheet, &mut vec![], &mut rules);
+  // The last html or body background is the page's
+  let page_background: Vec<&Rule> =
+    sheet
+    .rules
+    .iter()
+    .rfind(|r| r.selects(|s| s == "html" || s == "body") && background(r).is_some())
+    .into_iter()
+    .collect();
+
+  let mut findings = vec![];
+  let mut seen = HashSet::new();
+  for (theme, properties) in themes(&sheet, &rules) {
+    for chain in rules.iter().filter(|chain| !chain.last().unwrap().selector.starts_with('@')) {
+      let Some((_, value, line)) = chain.last().unwrap().get("color") else { continue };
+      let Some((fg, fg_hex, line)) = resolve(value, *line, chain, &properties, 0) else { continue };
+      let behind =
+        (1..=chain.len())
+        .rev()
+        .map(|i| &chain[..i])
+        .chain(std::iter::once(page_background.as_slice()))
+        .find_map(|chain| {
+          let (_, value, line) = background(chain.last()?)?;
+          resolve(value, *line, chain, &properties, 0)
+        });
+      let Some((bg, bg_hex, _)) = behind else { continue };
+
+      let ratio = contrast_ratio(&fg_hex, &bg_hex);
+      if ratio < MIN_CONTRAST && seen.insert((fg.clone(), fg_hex.clone(), bg.clone(), bg_hex.clone())) {
+        let within = if theme.is_empty() { String::new() } else { format!(" in {theme}") };
+        findings.push(Finding {
+          file: file.to_str().unwrap().to_string(),
+          line,
+          message: format!(
+            "low contrast {ratio:.2}:1 for {fg} ({fg_hex}) on {bg} ({bg_hex}), needs {MIN_CONTRAST}:1{within}",
+          ),
+        });
+      }
+    }
+  }
+
+  findings
+}
+
+fn background(rule: &Rule) -> Option<&Declaration> {
+  rule.get("background").or_else(|| rule.get("background-color"))
+}
+
+// Comments are blanked out first so lines still count
+fn parse(css: &str) -> Rule {
+  let comment_re = Regex::new(r"(?s)/\*.*?\*/").unwrap();
+  let css = comment_re.replace_all(css, |caps: &regex::Captures| {
+    caps[0].chars().map(|c| if c == '\n' { '\n' } else { ' ' }).collect::<String>()
+  });
+  let mut stack = vec![Rule::default()];
+  let mut start = 0;
+  let mut quote = None;
+
+  for (i, c) in css.char_indices() {
+    match (quote, c) {
+      (Some(q), c) if q == c => quote = None,
+      (None, '"' | '\'') => quote = Some(c),
+      (None, '{') => {
+        stack.push(Rule { selector: css[start..i].trim().to_string(), ..Default::default() });
+        start = i + 1;
+      },
+      (None, ';' | '}') => {
+        let text = &css[start..i];
+        if let Some((property, value)) = text.split_once(':') {
+          let line = html::line_at(&css, start + text.len() - text.trim_start().len());
+          let declaration = (property.trim().to_ascii_lowercase(), value.trim().to_string(), line);
+          stack.last_mut().unwrap().declarations.push(declaration);
+        }
+        if c == '}' && stack.len() > 1 {
+          let rule = stack.pop().unwrap();
+          stack.last_mut().unwrap().rules.push(rule);
+        }
+        start = i + 1;
+      },
+      _ => (),
+    }
+  }
+
+  // Unclosed rules end with the file
+  while stack.len() > 1 {
+    let rule = stack.pop().unwrap();
+    stack.last_mut().unwrap().rules.push(rule);
+  }
+  stack.pop().unwrap()
+}
+
+// Every rule below the sheet with the rules enclosing it, outermost first
+fn flatten<'a>(rule: &'a Rule, chain: &mut Vec<&'a Rule>, rules: &mut Vec<Vec<&'a Rule>>) {
+  for child in &rule.rules {
+    chain.push(child);
+    rules.push(chain.clone());
+    flatten(child, chain, rules);
+    chain.pop();
+  }
+}
+
+// The custom properties of the page, from top level :root, html and body
+// rules, then again with each other rule for :root or html laid over them,
+// named by its selector and any at-rules around it
+fn themes(sheet: &Rule, rules: &[Vec<&Rule>]) -> Vec<(String, Vec<Declaration>)> {
+  let is_page = |s: &str| s == ":root" || s == "html" || s == "body";
+  let custom = |rule: &Rule| -> Vec<Declaration> {
+    rule.declarations.iter().filter(|(p, _, _)| p.starts_with("--")).cloned().collect()
+  };
+  let base: Vec<Declaration> =
+    sheet.rules.iter().filter(|r| r.selects(is_page)).flat_map(custom).collect();
+
+  let mut themes = vec![(String::new(), base.clone())];
+  for chain in rules {
+    let rule = chain.last().unwrap();
+    let overrides = rule.selects(|s| s.starts_with(":root") || s.starts_with("html"));
+    let top_level = chain.len() == 1 && rule.selects(is_page);
+    if overrides && !top_level && !custom(rule).is_empty() {
+      let name = chain.iter().map(|r| r.selector.as_str()).collect::<Vec<_>>().join(" ");
+      themes.push((name, base.iter().cloned().chain(custom(rule)).collect()));
+    }
+  }
+
+  themes
+}
+
+// A #rgb or #rrggbb colour, directly or through custom properties defined in
+// the rules of chain (innermost first) or the theme, with the name it was
+// given by and the line of its definition
+fn resolve(
+  value: &str,
+  line: usize,
+  chain: &[&Rule],
+  theme: &[Declaration],
+  depth: usize,
+) -> Option<(String, String, usize)> {
+  let hex_re = Regex::new(r"^#[0-9a-fA-F]{3}(?:[0-9a-fA-F]{3})?$").unwrap();
+  let var_re = Regex::new(r"^var\(\s*(--[\w-]+)\s*(?:,[^)]*)?\)$").unwrap();
+  let value = value.trim();
+  if hex_re.is_match(value) {
+    return Some((value.to_string(), value.to_string(), line));
+  }
+
+  let name = &var_re.captures(value)?[1];
+  if depth > 10 { return None; }
+  let (_, definition, line) =
+    chain
+    .iter()
+    .rev()
+    .find_map(|rule| rule.get(name))
+    .or_else(|| theme.iter().rev().find(|(p, _, _)| p == name))?;
+  let (_, hex, _) = resolve(definition, *line, chain, theme, depth + 1)?;
+  Some((name.to_string(), hex, *line))
+}
+
+pub fn contrast_ratio(a: &str, b: &str) -> f64 {
+  let (a, b) = (luminance(a), luminance(b));
+  (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+// Relative luminance of a #rgb or #rrggbb colour
+fn luminance(hex: &str) -> f64 {
+  let hex = hex.trim_start_matches('#');
+  let hex =
+    if hex.len() == 3 { hex.chars().flat_map(|c| [c, c]).collect() } else { hex.to_string() };
+  let channel = |i: usize| {
+    let c = f64::from(u8::from_str_radix(&hex[i..i + 2], 16).unwrap()) / 255.0;
+    if c <= 0.039_28 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+  };
+
+  0.2126 * channel(0) + 0.7152 * channel(2) + 0.0722 * channel(4)
+}
+
+fn is_heading(name: &str) -> bool {
+  name.len() == 2 && name.starts_with('h') && ('1'..='6').contains(&name.chars().nth(1).unwrap())
+}
+
+// Text of the link starting at tags[i], counting the alt text of images
+fn link_text(contents: &str, tags: &[Tag], i: usize) -> String {
+  let Some(end) = tags[i + 1..].iter().position(|t| t.name == "a" && t.closing) else {
+    return String::new();
+  };
+  let end = i + 1 + end;
+
+  let alt: String =
+    tags[i + 1..end]
+    .iter()
+    .filter(|t| t.name == "img")
+    .filter_map(|t| t.attribute("alt"))
+    .collect();
+  format!("{}{alt}", html::text_between(contents, &tags[i], &tags[end])).trim().to_string()
+}
@@ -0,0 +1,116 @@
+use std::fmt::Write;
+use std::fs;
+
+use crate::io;
+use crate::post::{self, Metadata, PathConfig};
+use crate::string::escape_html;
+
+const MONTHS: [&str; 12] = [
+  "January", "February", "March", "April", "May", "June",
+  "July", "August", "September", "October", "November", "December",
+];
+
+// The posts either side of p in the newest first list from post::build_all,
+// as (older, newer). Only published posts are linked.
+pub fn neighbours<'a>(
+  p: &Metadata,
+  posts: &'a [Metadata],
+) -> (Option<&'a Metadata>, Option<&'a Metadata>) {
+  let published: Vec<&Metadata> =
+    posts.iter().filter(|p| p.visibility().is_public()).collect();
+  let Some(at) = published.iter().position(|other| other.name == p.name) else {
+    return (None, None);
+  };
+
+  (published.get(at + 1).copied(), at.checked_sub(1).map(|i| published[i]))
+}
+
+// Older and newer post links, empty when there are neither
+pub fn navigation(p: &Metadata, posts: &[Metadata]) -> String {
+  let (older, newer) = neighbours(p, posts);
+  let mut links = vec![];
+  if let Some(older) = older {
+    let title = escape_html(&older.title);
+    links.push(format!("<a class=\"older\" href=\"{}\">← Older: {title}</a>", older.url()));
+  }
+  if let Some(newer) = newer {
+    let title = escape_html(&newer.title);
+    links.push(format!("<a class=\"newer\" href=\"{}\">Newer: {title} →</a>", newer.url()));
+  }
+
+  if links.is_empty() { return String::new(); }
+  format!("<p class=\"older-newer\">\n{}\n</p>\n", links.join("\n"))
+}
+
+// Writes archive.html listing every post by year and month, and a page per
+// year in archive/
+pub fn generate(posts: &[Metadata], paths: &PathConfig) {
+  let dir = paths.public.join("archive");
+  if dir.exists() {
+    fs::remove_dir_all(&dir).unwrap();
+  }
+  fs::create_dir_all(&dir).unwrap();
+
+  let template = io::load_template("archive").replace("{nav}", &io::load_template("nav"));
+  let years = years(posts);
+
+  let mut all = String::new();
+  for (year, posts) in &years {
+    writeln!(all, "<h2><a href=\"/archive/{year}.html\">{year}</a></h2>").unwrap();
+    all.push_str(&months(posts));
+
+    let html =
+      template
+      .replace("{title}", year)
+      .replace("{content}", &format!("<h2>{year}</h2>\n{}", months(posts)));
+    fs::write(dir.join(format!("{year}.html")), html).unwrap();
+  }
+
+  let html = template.replace("{title}", "Archive").replace("{content}", &all);
+  fs::write(paths.public.join("archive.html"), html).unwrap();
+}
+
+// Published posts grouped by year, newest first. Posts without a date are
+// left out.
+pub fn years(posts: &[Metadata]) -> Vec<(String, Vec<&Metadata>)> {
+  let mut dated: Vec<&Metadata> =
+    posts
+    .iter()
+    .filter(|p| p.visibility().is_public() && post::is_date(&p.created))
+    .collect();
+  dated.sort_by(|a, b| b.created.cmp(&a.created).then_with(|| b.name.cmp(&a.name)));
+
+  let mut years: Vec<(String, Vec<&Metadata>)> = vec![];
+  for p in dated {
+    let year = &p.created[..4];
+    match years.last_mut() {
+      Some((y, posts)) if y == year => posts.push(p),
+      _ => years.push((year.to_string(), vec![p])),
+    }
+  }
+  years
+}
+
+fn months(posts: &[&Metadata]) -> String {
+  let mut html = String::new();
+  let mut current = "";
+  for p in posts {
+    let month = &p.created[5..7];
+    if month != current {
+      if !current.is_empty() { html.push_str("</ul>\n"); }
+      let name =
+        month.parse::<usize>().ok().and_then(|m| MONTHS.get(m.wrapping_sub(1))).unwrap_or(&month);
+      write!(html, "<h3>{name}</h3>\n<ul>\n").unwrap();
+      current = month;
+    }
+    writeln!(
+      html,
+      "<li><a href=\"{}\">{}</a> <span class=\"created\">{}</span></li>",
+      p.url(),
+      escape_html(&p.title),
+      p.created,
+    ).unwrap();
+  }
+  if !current.is_empty() { html.push_str("</ul>\n"); }
+  html
+}
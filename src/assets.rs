@@ -0,0 +1,286 @@
+use regex::{Captures, Regex};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::io;
+use crate::string::{escape_json, hex};
+
+// Directories under the output root whose files get content-addressed names
+pub const FINGERPRINTED: [&str; 5] = ["css", "images", "fonts", "highlightjs", "js"];
+
+// Original path -> fingerprinted path, both relative to the output root
+pub type Manifest = BTreeMap<String, String>;
+
+struct References {
+  // Comments, style and script elements with their contents, and other tags
+  html: Regex,
+  attribute: Regex,
+  css: Regex,
+  json: Regex,
+}
+
+// Where a reference is found and what it can be rewritten to
+struct Rewrite<'a> {
+  from: &'a str,
+  base_url: &'a str,
+  manifest: &'a Manifest,
+}
+
+// Renames assets to include a hash of their contents, rewrites references to
+// them in HTML and CSS and writes manifest.json. Only URL positions are
+// rewritten: href, src, srcset, meta content, url() and JSON-LD strings, in
+// root-relative or absolute (under base_url) form. Text and code in pages are
+// left alone. Works in place so only run it over a staged copy of public/.
+pub fn fingerprint(root: &Path, base_url: &str) -> Manifest {
+  let base_url = base_url.trim_end_matches('/');
+  let refs = References {
+    html: Regex::new(
+      r#"(?is)<!--.*?-->|(<(style|script)\b(?:"[^"]*"|'[^']*'|[^'">])*>)(.*?)(</(?:style|script)\s*>)|<[a-z](?:"[^"]*"|'[^']*'|[^'">])*>"#,
+    ).unwrap(),
+    attribute: Regex::new(
+      r#"(?i)(\s(href|src|srcset|content|style)\s*=\s*)(?:"([^"]*)"|'([^']*)')"#,
+    ).unwrap(),
+    css: Regex::new(
+      r#"url\(\s*(?:"([^"]*)"|'([^']*)'|([^'"\s)]+))\s*\)|@import\s+(?:"([^"]*)"|'([^']*)')"#,
+    ).unwrap(),
+    json: Regex::new(r#""([^"\\]*)""#).unwrap(),
+  };
+  let mut manifest = Manifest::new();
+  let mut stylesheets = vec![];
+
+  for dir in FINGERPRINTED {
+    for file in io::files_in_tree(&root.join(dir)) {
+      let key = relative(root, &file);
+      if file.extension().is_some_and(|ext| ext == "css") {
+        stylesheets.push(key);
+      } else {
+        let hashed = hashed_name(&key, &fs::read(&file).unwrap());
+        fs::rename(&file, root.join(&hashed)).unwrap();
+        manifest.insert(key, hashed);
+      }
+    }
+  }
+
+  // Stylesheets are hashed after their rewrites so a change to an imported
+  // stylesheet also changes the name of the one importing it
+  while !stylesheets.is_empty() {
+    let ready: Vec<String> =
+      stylesheets
+      .iter()
+      .filter(|key| {
+        let css = fs::read_to_string(root.join(key)).unwrap();
+        !references(&refs.css, &css, key, base_url).iter().any(|r| {
+          r != *key && stylesheets.contains(r)
+        })
+      })
+      .cloned()
+      .collect();
+    // Import cycles can't be ordered, so hash what's left as it stands
+    let ready = if ready.is_empty() { stylesheets.clone() } else { ready };
+
+    for key in ready {
+      let css = fs::read_to_string(root.join(&key)).unwrap();
+      let at = Rewrite { from: &key, base_url, manifest: &manifest };
+      let css = rewrite(&refs.css, &css, &at);
+      let hashed = hashed_name(&key, css.as_bytes());
+      fs::write(root.join(&hashed), css).unwrap();
+      fs::remove_file(root.join(&key)).unwrap();
+      stylesheets.retain(|s| s != &key);
+      manifest.insert(key, hashed);
+    }
+  }
+
+  for file in io::files_in_tree(root) {
+    if file.extension().is_some_and(|ext| ext == "html") {
+      let key = relative(root, &file);
+      let html = fs::read_to_string(&file).unwrap();
+      let at = Rewrite { from: &key, base_url, manifest: &manifest };
+      let rewritten = rewrite_html(&refs, &html, &at);
+      if rewritten != html {
+        fs::write(&file, rewritten).unwrap();
+      }
+    }
+  }
+
+  for (key, hashed) in &manifest {
+    println!("  {key} -> {hashed}");
+  }
+  write_manifest(root, &manifest);
+
+  manifest
+}
+
+// Content hash inserted before the extension: css/main.css -> css/main.<hash>.css
+pub fn hashed_name(key: &str, contents: &[u8]) -> String {
+  let hash = hex(&Sha256::digest(contents)[..5]);
+  let (dir, file) = key.rsplit_once('/').unwrap_or(("", key));
+  let file = match file.rsplit_once('.') {
+    Some((stem, ext)) => format!("{stem}.{hash}.{ext}"),
+    None => format!("{file}.{hash}"),
+  };
+
+  if dir.is_empty() { file } else { format!("{dir}/{file}") }
+}
+
+fn write_manifest(root: &Path, manifest: &Manifest) {
+  let entries: Vec<String> =
+    manifest
+    .iter()
+    .map(|(k, v)| format!("  \"{}\": \"{}\"", escape_json(k), escape_json(v)))
+    .collect();
+
+  fs::write(
+    root.join("manifest.json"),
+    format!("{{\n{}\n}}\n", entries.join(",\n")),
+  ).unwrap();
+}
+
+fn relative(root: &Path, file: &Path) -> String {
+  file.strip_prefix(root).unwrap().to_str().unwrap().replace('\\', "/")
+}
+
+// URL captured by whichever alternative of the pattern matched
+fn url<'a>(caps: &Captures<'a>) -> Option<regex::Match<'a>> {
+  caps.iter().skip(1).flatten().next()
+}
+
+fn references(re: &Regex, contents: &str, from: &str, base_url: &str) -> Vec<String> {
+  re.captures_iter(contents)
+    .filter_map(|caps| url(&caps).and_then(|u| resolve(local(u.as_str(), base_url).1, from)))
+    .collect()
+}
+
+fn rewrite_html(refs: &References, html: &str, at: &Rewrite) -> String {
+  refs.html.replace_all(html, |caps: &Captures| {
+    let whole = &caps[0];
+    if whole.starts_with("<!--") {
+      return whole.to_string();
+    }
+    let (Some(open), Some(name)) = (caps.get(1), caps.get(2)) else {
+      return rewrite_tag(refs, whole, at);
+    };
+
+    let contents = &caps[3];
+    let contents =
+      if name.as_str().eq_ignore_ascii_case("style") {
+        rewrite(&refs.css, contents, at)
+      } else if open.as_str().contains("application/ld+json") && !at.base_url.is_empty() {
+        // Structured data only holds absolute URLs
+        rewrite_json(&refs.json, contents, at)
+      } else {
+        contents.to_string()
+      };
+    format!("{}{contents}{}", rewrite_tag(refs, open.as_str(), at), &caps[4])
+  }).to_string()
+}
+
+fn rewrite_tag(refs: &References, tag: &str, at: &Rewrite) -> String {
+  let meta = tag.get(..5).is_some_and(|t| t.eq_ignore_ascii_case("<meta"));
+  refs.attribute.replace_all(tag, |caps: &Captures| {
+    let name = caps[2].to_ascii_lowercase();
+    let value = caps.get(3).or(caps.get(4)).unwrap();
+    let new_value = match name.as_str() {
+      "style" => rewrite(&refs.css, value.as_str(), at),
+      "srcset" => srcset(value.as_str(), at),
+      "content" if !meta => value.as_str().to_string(),
+      _ => hashed_url(value.as_str(), at).unwrap_or_else(|| value.as_str().to_string()),
+    };
+
+    let whole = caps.get(0).unwrap();
+    let start = value.start() - whole.start();
+    let end = value.end() - whole.start();
+    format!("{}{new_value}{}", &whole.as_str()[..start], &whole.as_str()[end..])
+  }).to_string()
+}
+
+// Candidates are comma separated: url followed by an optional descriptor
+fn srcset(value: &str, at: &Rewrite) -> String {
+  value
+    .split(',')
+    .map(|candidate| {
+      let start = candidate.len() - candidate.trim_start().len();
+      let end = candidate[start..].find(char::is_whitespace).map_or(candidate.len(), |e| start + e);
+      match hashed_url(&candidate[start..end], at) {
+        Some(new_url) => format!("{}{new_url}{}", &candidate[..start], &candidate[end..]),
+        None => candidate.to_string(),
+      }
+    })
+    .collect::<Vec<String>>()
+    .join(",")
+}
+
+fn rewrite_json(re: &Regex, json: &str, at: &Rewrite) -> String {
+  re.replace_all(json, |caps: &Captures| {
+    match hashed_url(&caps[1], at).filter(|_| local(&caps[1], at.base_url).0.is_some()) {
+      Some(new_url) => format!("\"{new_url}\""),
+      None => caps[0].to_string(),
+    }
+  }).to_string()
+}
+
+fn rewrite(re: &Regex, contents: &str, at: &Rewrite) -> String {
+  re.replace_all(contents, |caps: &Captures| {
+    let whole = caps.get(0).unwrap();
+    let Some(u) = url(caps) else { return whole.as_str().to_string() };
+    let Some(new_url) = hashed_url(u.as_str(), at) else { return whole.as_str().to_string() };
+
+    let start = u.start() - whole.start();
+    let end = u.end() - whole.start();
+    format!("{}{new_url}{}", &whole.as_str()[..start], &whole.as_str()[end..])
+  }).to_string()
+}
+
+// The URL pointing at the fingerprinted file, if it points at an asset
+fn hashed_url(url: &str, at: &Rewrite) -> Option<String> {
+  let (base, url) = local(url, at.base_url);
+  let hashed = at.manifest.get(&resolve(url, at.from)?)?;
+
+  // Only the file name changes as hashed files stay in the same directory
+  let (path, suffix) = url.split_at(url.find(['?', '#']).unwrap_or(url.len()));
+  let file = hashed.rsplit('/').next().unwrap();
+  let new_url = match path.rsplit_once('/') {
+    Some((dir, _)) => format!("{dir}/{file}{suffix}"),
+    None => format!("{file}{suffix}"),
+  };
+
+  Some(format!("{}{new_url}", base.unwrap_or("")))
+}
+
+// Splits an absolute URL under base_url into the base and a root-relative URL
+fn local<'a>(url: &'a str, base_url: &'a str) -> (Option<&'a str>, &'a str) {
+  match url.strip_prefix(base_url) {
+    Some(path) if !base_url.is_empty() && path.starts_with('/') => (Some(base_url), path),
+    _ => (None, url),
+  }
+}
+
+// Resolves a URL found in the file `from` to a path relative to the root.
+// External and fragment-only URLs resolve to nothing.
+fn resolve(url: &str, from: &str) -> Option<String> {
+  let external = ["//", "#", "data:", "mailto:", "tel:", "javascript:"];
+  if url.contains("://") || external.iter().any(|e| url.starts_with(e)) {
+    return None;
+  }
+
+  let path = &url[..url.find(['?', '#']).unwrap_or(url.len())];
+  let parts: Vec<&str> =
+    if let Some(absolute) = path.strip_prefix('/') {
+      absolute.split('/').collect()
+    } else {
+      let dir = from.rsplit_once('/').map_or("", |(dir, _)| dir);
+      dir.split('/').chain(path.split('/')).collect()
+    };
+
+  let mut resolved: Vec<&str> = vec![];
+  for part in parts {
+    match part {
+      "" | "." => (),
+      ".." => { resolved.pop(); },
+      _ => resolved.push(part),
+    }
+  }
+
+  Some(resolved.join("/"))
+}
@@ -0,0 +1,93 @@
+use regex::Regex;
+
+use crate::markdown::{self, Line};
+use crate::string::escape_html;
+
+// Kinds of callout with their default titles, as GitHub's alerts
+pub const KINDS: [(&str, &str); 5] = [
+  ("note", "Note"),
+  ("tip", "Tip"),
+  ("important", "Important"),
+  ("warning", "Warning"),
+  ("caution", "Caution"),
+];
+
+// Converts GitHub style alerts:
+//
+//   > [!WARNING] Optional title
+//   > Text
+//
+// and fenced callouts:
+//
+//   :::warning Optional title
+//   Text
+//   :::
+//
+// into <aside class="callout warning"> with a title. The text stays Markdown
+// and may hold code blocks or further callouts. Unknown kinds are left alone.
+pub fn from_markdown(markdown: &str) -> String {
+  let alert_re = Regex::new(r"^>\s*\[!([A-Za-z]+)\]\s*(.*)$").unwrap();
+  let fenced_re = Regex::new(r"^:::\s*([A-Za-z]+)\s*(.*)$").unwrap();
+  let mut output = String::new();
+  let mut lines = markdown::lines(markdown).into_iter().peekable();
+
+  while let Some((kind, line)) = lines.next() {
+    let trimmed = line.trim();
+    if kind != Line::Prose {
+      output.push_str(line);
+      continue;
+    }
+
+    let alert = alert_re.captures(trimmed).filter(|caps| title(&caps[1]).is_some());
+    let fenced = fenced_re.captures(trimmed).filter(|caps| title(&caps[1]).is_some());
+    if let Some(caps) = alert {
+      let mut body = String::new();
+      while let Some((_, next)) =
+        lines.next_if(|(kind, l)| *kind == Line::Prose && l.trim_start().starts_with('>'))
+      {
+        let text = next.trim_start().strip_prefix('>').unwrap();
+        body.push_str(text.strip_prefix(' ').unwrap_or(text));
+      }
+      output.push_str(&render(&caps[1], &caps[2], &body));
+    } else if let Some(caps) = fenced {
+      let mut body = String::new();
+      let mut depth = 0;
+      // Code blocks in the body may hold ::: of their own
+      for (kind, next) in lines.by_ref() {
+        let trimmed = next.trim();
+        if kind == Line::Prose && trimmed == ":::" {
+          if depth == 0 { break; }
+          depth -= 1;
+        } else if kind == Line::Prose && fenced_re.is_match(trimmed) {
+          depth += 1;
+        }
+        body.push_str(next);
+      }
+      output.push_str(&render(&caps[1], &caps[2], &body));
+    } else {
+      output.push_str(line);
+    }
+  }
+
+  output
+}
+
+fn title(kind: &str) -> Option<&'static str> {
+  KINDS
+    .iter()
+    .find(|(k, _)| k.eq_ignore_ascii_case(kind))
+    .map(|(_, title)| *title)
+}
+
+// Blank lines around the body let MultiMarkdown treat it as Markdown
+fn render(kind: &str, custom_title: &str, body: &str) -> String {
+  let kind_title = title(kind).unwrap();
+  let custom_title = custom_title.trim();
+  let heading = if custom_title.is_empty() { kind_title } else { custom_title };
+  format!(
+    "<aside class=\"callout {}\" role=\"note\">\n<p class=\"callout-title\">{}</p>\n\n{}\n\n</aside>\n\n",
+    kind.to_ascii_lowercase(),
+    escape_html(heading),
+    from_markdown(body).trim_end(),
+  )
+}
@@ -0,0 +1,206 @@
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::html;
+use crate::io;
+use crate::post::{Metadata, PathConfig};
+
+// Attributes that reference other files, by the tags they appear on
+const REFERENCES: [(&str, &str); 9] = [
+  ("a", "href"),
+  ("link", "href"),
+  ("area", "href"),
+  ("img", "src"),
+  ("script", "src"),
+  ("source", "src"),
+  ("iframe", "src"),
+  ("video", "src"),
+  ("audio", "src"),
+];
+
+// A problem found in the generated output, located in the file it came from
+#[derive(Debug, PartialEq)]
+pub struct Finding {
+  pub file: String,
+  pub line: usize,
+  pub message: String,
+}
+
+impl fmt::Display for Finding {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}:{}: {}", self.file, self.line, self.message)
+  }
+}
+
+// The post or page each generated HTML file came from, by its path relative
+// to the output root
+pub type Sources = HashMap<PathBuf, Vec<PathBuf>>;
+
+// Maps the output of each post (with its permalink set) and of every page in
+// pages/ to its source. Call once per collection and extend for the rest.
+pub fn sources(posts: &[Metadata], paths: &PathConfig) -> Sources {
+  let mut sources = Sources::new();
+
+  for p in posts {
+    let output = p.output(paths);
+    let relative = output.strip_prefix(&paths.public).unwrap_or(&output).to_path_buf();
+    let files = ["md", "rs"].iter().map(|ext| paths.posts.join(format!("{}.{ext}", p.name)));
+    sources.insert(relative, files.collect());
+  }
+  if paths.pages.is_dir() {
+    for page in io::paths_in_dir(&paths.pages, &["md"]) {
+      let stem = page.file_stem().unwrap().to_str().unwrap();
+      sources.insert(PathBuf::from(format!("{stem}.html")), vec![page.clone()]);
+    }
+  }
+
+  sources
+}
+
+// Resolves every href, src and CSS url() in the output under root, including
+// #fragments against the ids of the target page
+pub fn links(root: &Path, sources: &Sources) -> Vec<Finding> {
+  let css_re = Regex::new(
+    r#"url\(\s*(?:"([^"]*)"|'([^']*)'|([^'"\s)]+))\s*\)|@import\s+(?:"([^"]*)"|'([^']*)')"#,
+  ).unwrap();
+  let mut ids: HashMap<PathBuf, HashSet<String>> = HashMap::new();
+  let mut findings = vec![];
+
+  for file in io::files_in_tree(root) {
+    let ext = file.extension().unwrap_or_default().to_str().unwrap();
+    let contents = match ext {
+      "html" | "css" => fs::read_to_string(&file).unwrap(),
+      _ => continue,
+    };
+
+    let references: Vec<(String, usize)> =
+      if ext == "html" {
+        html::tags(&contents)
+          .iter()
+          .filter(|t| !t.closing)
+          .filter_map(|t| {
+            let (_, attribute) = REFERENCES.iter().find(|(tag, _)| *tag == t.name)?;
+            t.attribute(attribute).map(|url| (url.to_string(), t.line))
+          })
+          .collect()
+      } else {
+        css_re
+          .captures_iter(&contents)
+          .filter_map(|caps| {
+            let url = caps.iter().skip(1).flatten().next()?;
+            Some((url.as_str().to_string(), html::line_at(&contents, url.start())))
+          })
+          .collect()
+      };
+
+    for (url, line) in references {
+      if let Some(message) = broken(root, &file, &url, &mut ids) {
+        findings.push(locate(root, &file, &url, line, sources, message));
+      }
+    }
+  }
+
+  findings
+}
+
+// Points a finding at the post, page or template the offending text came
+// from, falling back to the generated file
+pub fn locate(
+  root: &Path,
+  file: &Path,
+  needle: &str,
+  line: usize,
+  sources: &Sources,
+  message: String,
+) -> Finding {
+  let relative = file.strip_prefix(root).unwrap();
+  let mut candidates = sources.get(relative).cloned().unwrap_or_default();
+  candidates.extend(io::paths_in_dir(Path::new("templates"), &["html"]));
+
+  for source in candidates {
+    let Ok(contents) = fs::read_to_string(&source) else { continue };
+    if let Some(at) = contents.find(needle) {
+      return Finding {
+        file: source.to_str().unwrap().to_string(),
+        line: html::line_at(&contents, at),
+        message,
+      };
+    }
+  }
+
+  Finding { file: file.to_str().unwrap().to_string(), line, message }
+}
+
+fn broken(
+  root: &Path,
+  from: &Path,
+  url: &str,
+  ids: &mut HashMap<PathBuf, HashSet<String>>,
+) -> Option<String> {
+  let external = ["//", "mailto:", "tel:", "data:", "javascript:"];
+  if url.contains("://") || external.iter().any(|e| url.starts_with(e)) {
+    return None;
+  }
+
+  let (path, fragment) = url.split_once('#').unwrap_or((url, ""));
+  let path = decode_percent(&path[..path.find('?').unwrap_or(path.len())]);
+  let mut target =
+    if path.is_empty() {
+      from.to_path_buf()
+    } else if let Some(absolute) = path.strip_prefix('/') {
+      root.join(absolute)
+    } else {
+      from.parent().unwrap().join(&path)
+    };
+  if target.is_dir() {
+    target = target.join("index.html");
+  }
+
+  if !target.exists() {
+    return Some(format!("broken reference {url}"));
+  }
+
+  let is_html = target.extension().is_some_and(|ext| ext == "html");
+  if fragment.is_empty() || !is_html {
+    return None;
+  }
+
+  let target_ids = ids.entry(target.clone()).or_insert_with(|| {
+    let contents = fs::read_to_string(&target).unwrap();
+    html::tags(&contents)
+      .iter()
+      .flat_map(|t| [t.attribute("id"), if t.name == "a" { t.attribute("name") } else { None }])
+      .flatten()
+      .map(String::from)
+      .collect()
+  });
+
+  if target_ids.contains(&decode_percent(fragment)) {
+    None
+  } else {
+    Some(format!("missing anchor #{fragment} in {url}"))
+  }
+}
+
+fn decode_percent(text: &str) -> String {
+  let bytes = text.as_bytes();
+  let mut decoded = vec![];
+  let mut i = 0;
+  while i < bytes.len() {
+    let hex = bytes.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok());
+    match hex.map(|h| u8::from_str_radix(h, 16)) {
+      Some(Ok(byte)) if bytes[i] == b'%' => {
+        decoded.push(byte);
+        i += 3;
+      },
+      _ => {
+        decoded.push(bytes[i]);
+        i += 1;
+      },
+    }
+  }
+  String::from_utf8_lossy(&decoded).to_string()
+}
@@ -0,0 +1,137 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::check::Finding;
+use crate::config::SiteConfig;
+use crate::feed;
+use crate::index::{self, Sort};
+use crate::io;
+use crate::path;
+use crate::permalink;
+use crate::post::{Metadata, PathConfig};
+use crate::string::escape_html;
+
+// Name of the collection listed on the home page
+pub const POSTS: &str = "posts";
+
+// A kind of content with its own sources, URLs, listing and feed. Posts are
+// the default collection, the others are built the same way from their own
+// directories.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Collection {
+  // Directory name under artifacts/ and public/
+  pub name: String,
+  // Heading of the listing page and feed
+  pub title: String,
+  // Where the .md and .rs sources are
+  pub source: PathBuf,
+  // URL pattern for its items, see permalink::apply
+  pub permalink: String,
+  // Listing page template in templates/
+  pub template: String,
+  // Order of the listing and feed
+  pub sort: Sort,
+  // Whether it has an Atom feed
+  pub feed: bool,
+}
+
+impl Default for Collection {
+  fn default() -> Self {
+    Collection {
+      name: POSTS.to_string(),
+      title: "Posts".to_string(),
+      source: PathBuf::from("posts"),
+      permalink: permalink::DEFAULT.to_string(),
+      template: "home".to_string(),
+      sort: Sort::Created,
+      feed: true,
+    }
+  }
+}
+
+impl Collection {
+  // The listing page, the home page for posts
+  pub fn url(&self) -> String {
+    if self.name == POSTS { "/".to_string() } else { format!("/{}/", self.name) }
+  }
+
+  pub fn feed_url(&self) -> String {
+    format!("/{}/feed.xml", self.name)
+  }
+
+  // Directories of the collection's sources, generated Markdown and HTML.
+  // Posts keep artifacts/ and public/posts/ to themselves, others get
+  // artifacts-<name>/ beside it so {{../templates/...}} transclusions still
+  // resolve, and public/<name>/.
+  pub fn paths(&self, paths: &PathConfig) -> PathConfig {
+    let (artifacts, public_posts) =
+      if self.name == POSTS {
+        (paths.artifacts.clone(), paths.public_posts.clone())
+      } else {
+        let artifacts = paths.artifacts.file_name().unwrap().to_str().unwrap();
+        (
+          paths.artifacts.with_file_name(format!("{artifacts}-{}", self.name)),
+          paths.public.join(&self.name),
+        )
+      };
+
+    PathConfig {
+      posts: self.source.clone(),
+      pages: paths.pages.clone(),
+      artifacts,
+      public: paths.public.clone(),
+      public_posts,
+      dist: paths.dist.clone(),
+    }
+  }
+}
+
+// Splits the items that can be built from those whose permalink is outside
+// the collection's directory, where stale HTML wouldn't be cleaned up and they
+// could overwrite a page, or is the same as an earlier item's. Those are
+// reported against their Markdown and left out.
+pub fn place(items: Vec<Metadata>, collection: &Collection, paths: &PathConfig) -> (Vec<Metadata>, Vec<Finding>) {
+  let mut placed: Vec<Metadata> = vec![];
+  let mut findings = vec![];
+
+  for p in items {
+    let output = p.output(paths);
+    let clash = placed.iter().find(|other| other.output(paths) == output);
+    let message =
+      if !output.starts_with(&paths.public_posts) {
+        format!("permalink {} must be under {}", p.url(), collection.url())
+      } else if let Some(other) = clash {
+        format!("permalink {} is already {}'s", p.url(), other.name)
+      } else {
+        placed.push(p);
+        continue;
+      };
+    findings.push(Finding { file: path::markdown(&paths.artifacts, &p.name), line: 1, message });
+  }
+
+  (placed, findings)
+}
+
+// Writes index.html in the collection's directory with a card for each
+// published item in the collection's order
+pub fn listing(items: &[Metadata], collection: &Collection, paths: &PathConfig, site: &SiteConfig) {
+  let template = io::load_template(&collection.template);
+  let nav = io::load_template("nav");
+  let published: Vec<&Metadata> = items.iter().filter(|p| p.visibility().is_public()).collect();
+  let cards =
+    index::order(published, collection.sort)
+    .into_iter()
+    .map(index::card)
+    .collect::<Vec<String>>()
+    .join("\n");
+
+  let html =
+    template
+    .replace("{head}", &feed::link(collection, site))
+    .replace("{nav}", &nav)
+    .replace("{title}", &escape_html(&collection.title))
+    .replace("{posts}", &cards);
+
+  fs::create_dir_all(&paths.public_posts).unwrap();
+  fs::write(paths.public_posts.join("index.html"), html).unwrap();
+}
@@ -0,0 +1,115 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::io;
+use crate::path;
+
+// Text based outputs worth compressing. Images and fonts already are.
+pub const COMPRESSIBLE: [&str; 7] =
+  ["html", "css", "js", "svg", "xml", "json", "txt"];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Encoding {
+  Gzip,
+  Brotli,
+}
+
+impl Encoding {
+  pub fn extension(self) -> &'static str {
+    match self {
+      Encoding::Gzip => "gz",
+      Encoding::Brotli => "br",
+    }
+  }
+
+  fn command(self) -> Command {
+    let (program, args) = match self {
+      Encoding::Gzip => ("gzip", ["-9", "-n", "-c"]),
+      Encoding::Brotli => ("brotli", ["-q", "11", "-c"]),
+    };
+    let mut command = Command::new(program);
+    command.args(args);
+    command
+  }
+}
+
+// Writes a compressed sibling (index.html -> index.html.gz) for every
+// compressible file below root. Siblings newer than their source are left
+// alone and those whose source has gone are removed. Encodings whose tool
+// isn't installed are skipped with a warning.
+pub fn precompress(root: &Path, encodings: &[Encoding]) {
+  let mut encodings = encodings.to_vec();
+
+  for file in io::files_in_tree(root) {
+    let ext = file.extension().unwrap_or_default().to_str().unwrap();
+
+    if is_sibling(&file) {
+      if !file.with_extension("").exists() {
+        println!("  Removing {}", file.to_str().unwrap());
+        fs::remove_file(&file).unwrap();
+      }
+    } else if COMPRESSIBLE.contains(&ext) {
+      encodings.retain(|encoding| {
+        let sibling = sibling(&file, *encoding);
+        let (source, target) = (file.to_str().unwrap(), sibling.to_str().unwrap());
+        if path::modified(source) <= path::modified(target) { return true; }
+
+        println!("  Compressing {target}");
+        match compress(&file, &sibling, *encoding) {
+          Ok(()) => true,
+          Err(e) => {
+            eprintln!("  Warning: skipping {encoding:?}, {e}");
+            false
+          },
+        }
+      });
+    }
+  }
+}
+
+// Removes all compressed siblings below root
+pub fn remove(root: &Path) {
+  for file in io::files_in_tree(root) {
+    if is_sibling(&file) {
+      fs::remove_file(file).unwrap();
+    }
+  }
+}
+
+fn sibling(file: &Path, encoding: Encoding) -> PathBuf {
+  let mut name = file.as_os_str().to_owned();
+  name.push(".");
+  name.push(encoding.extension());
+  PathBuf::from(name)
+}
+
+fn is_sibling(file: &Path) -> bool {
+  let ext = file.extension().unwrap_or_default();
+  let source_ext = file.file_stem().map(Path::new).and_then(Path::extension);
+
+  [Encoding::Gzip, Encoding::Brotli].iter().any(|e| ext == e.extension())
+    && source_ext.is_some_and(|s| COMPRESSIBLE.contains(&s.to_str().unwrap()))
+}
+
+// Fails when the tool can't be run at all. A file it can't compress is
+// reported and left without a sibling.
+fn compress(file: &Path, sibling: &Path, encoding: Encoding) -> Result<(), String> {
+  let output =
+    encoding
+    .command()
+    .arg(file)
+    .output()
+    .map_err(|e| format!("could not run {}: {e}", encoding.command().get_program().display()))?;
+
+  if output.status.success() {
+    fs::write(sibling, output.stdout).unwrap();
+  } else {
+    eprintln!(
+      "{encoding:?} failed for {}: {}",
+      file.to_str().unwrap(),
+      String::from_utf8_lossy(&output.stderr),
+    );
+  }
+  Ok(())
+}
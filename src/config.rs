@@ -0,0 +1,46 @@
+use crate::collection::{self, Collection};
+use crate::compress::Encoding;
+use crate::deploy::Target;
+
+// Site wide settings. Directory layout lives in post::PathConfig.
+#[derive(Default)]
+pub struct SiteConfig {
+  // Absolute URL of the home page used in sitemaps, feeds and metadata
+  pub base_url: String,
+  // Site and author names used in page metadata
+  pub name: String,
+  pub author: String,
+  // Posts and any other kinds of content, see collection::Collection
+  pub collections: Vec<Collection>,
+  // Cards per home page, everything on index.html when 0
+  pub posts_per_page: usize,
+  // Number of related posts listed at the end of each post
+  pub related_posts: usize,
+  // Wrap figure images in a link to the full-size image
+  pub link_figures: bool,
+  // Cache-Control max-age in seconds for pages, and for fingerprinted assets
+  // which are cached as immutable
+  pub cache_max_age: u32,
+  pub asset_cache_max_age: u32,
+  // Strict-Transport-Security and Referrer-Policy values, left out when empty
+  pub hsts: String,
+  pub referrer_policy: String,
+  // Precompressed siblings written next to the build output (none when empty)
+  pub precompress: Vec<Encoding>,
+  // Whether the deployed files include the precompressed siblings
+  pub deploy_precompressed: bool,
+  // Where --deploy publishes dist/ to
+  pub deploy: Target,
+}
+
+impl SiteConfig {
+  // The posts collection, or its defaults when it isn't configured
+  pub fn posts(&self) -> Collection {
+    self
+      .collections
+      .iter()
+      .find(|c| c.name == collection::POSTS)
+      .cloned()
+      .unwrap_or_default()
+  }
+}
@@ -0,0 +1,24 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Today's date in UTC as YYYY-MM-DD, comparable with metadata dates
+pub fn today() -> String {
+  let seconds = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+  from_days(seconds / 86_400)
+}
+
+// The date a number of days after 1970-01-01
+pub fn from_days(days: u64) -> String {
+  // Howard Hinnant's civil_from_days with eras starting on 0000-03-01
+  let days = days + 719_468;
+  let era = days / 146_097;
+  let day_of_era = days % 146_097;
+  let year_of_era =
+    (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+  let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+  let month_index = (5 * day_of_year + 2) / 153;
+  let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+  let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+  let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+  format!("{year:04}-{month:02}-{day:02}")
+}
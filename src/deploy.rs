@@ -0,0 +1,193 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::TempDir;
+
+use crate::io;
+
+// Somewhere the staged dist/ directory can be published to
+pub trait Deployer {
+  // Shown in the deploy output
+  fn name(&self) -> String;
+  fn deploy(&self, dir: &Path) -> Result<(), String>;
+}
+
+// Where `--deploy` publishes to, set in SiteConfig
+#[derive(Debug, PartialEq)]
+pub enum Target {
+  Cloudflare(Cloudflare),
+  S3(S3),
+  Rsync(Rsync),
+  GitBranch(GitBranch),
+  Local(Local),
+}
+
+impl Default for Target {
+  fn default() -> Self {
+    Target::Cloudflare(Cloudflare { project: "electricvisions".to_string() })
+  }
+}
+
+impl Target {
+  pub fn deployer(&self) -> &dyn Deployer {
+    match self {
+      Target::Cloudflare(d) => d,
+      Target::S3(d) => d,
+      Target::Rsync(d) => d,
+      Target::GitBranch(d) => d,
+      Target::Local(d) => d,
+    }
+  }
+}
+
+// Cloudflare Pages via wrangler
+#[derive(Debug, PartialEq)]
+pub struct Cloudflare {
+  pub project: String,
+}
+
+impl Cloudflare {
+  pub fn command(&self, dir: &Path) -> Command {
+    let mut command = Command::new("wrangler");
+    command
+      .args(["pages", "deploy", dir.to_str().unwrap()])
+      .arg(format!("--project-name={}", self.project));
+    command
+  }
+}
+
+impl Deployer for Cloudflare {
+  fn name(&self) -> String {
+    format!("Cloudflare Pages ({})", self.project)
+  }
+
+  fn deploy(&self, dir: &Path) -> Result<(), String> {
+    run(self.command(dir))
+  }
+}
+
+// An S3 bucket, or any S3 compatible store such as MinIO when endpoint is set,
+// via the AWS CLI. Files no longer in dir are deleted from the bucket.
+#[derive(Debug, Default, PartialEq)]
+pub struct S3 {
+  pub bucket: String,
+  // e.g. http://localhost:9000, AWS itself when empty
+  pub endpoint: String,
+}
+
+impl S3 {
+  pub fn command(&self, dir: &Path) -> Command {
+    let mut command = Command::new("aws");
+    command
+      .args(["s3", "sync", dir.to_str().unwrap()])
+      .arg(format!("s3://{}", self.bucket))
+      .arg("--delete");
+    if !self.endpoint.is_empty() {
+      command.args(["--endpoint-url", &self.endpoint]);
+    }
+    command
+  }
+}
+
+impl Deployer for S3 {
+  fn name(&self) -> String {
+    format!("s3://{}", self.bucket)
+  }
+
+  fn deploy(&self, dir: &Path) -> Result<(), String> {
+    run(self.command(dir))
+  }
+}
+
+// A directory on another machine over SSH, e.g. user@host:/var/www/site
+#[derive(Debug, Default, PartialEq)]
+pub struct Rsync {
+  pub destination: String,
+}
+
+impl Rsync {
+  pub fn command(&self, dir: &Path) -> Command {
+    let mut command = Command::new("rsync");
+    // The trailing slash copies dir's contents rather than dir itself
+    command
+      .args(["-az", "--delete"])
+      .arg(format!("{}/", dir.to_str().unwrap()))
+      .arg(&self.destination);
+    command
+  }
+}
+
+impl Deployer for Rsync {
+  fn name(&self) -> String {
+    format!("rsync {}", self.destination)
+  }
+
+  fn deploy(&self, dir: &Path) -> Result<(), String> {
+    run(self.command(dir))
+  }
+}
+
+// A branch of a git repository, e.g. gh-pages for GitHub Pages. Each deploy
+// force pushes a single commit holding dir so the branch doesn't grow.
+#[derive(Debug, Default, PartialEq)]
+pub struct GitBranch {
+  // Anything git push accepts: a URL or a path to a repository
+  pub remote: String,
+  pub branch: String,
+}
+
+impl Deployer for GitBranch {
+  fn name(&self) -> String {
+    format!("{} branch of {}", self.branch, self.remote)
+  }
+
+  fn deploy(&self, dir: &Path) -> Result<(), String> {
+    let git_dir = TempDir::with_prefix("deploy").map_err(|e| e.to_string())?;
+    let git = |args: &[&str]| {
+      let mut command = Command::new("git");
+      // The commit is throwaway so needn't be attributed to anyone
+      command
+        .args(["-c", "user.name=Deploy", "-c", "user.email=deploy@localhost"])
+        .arg(format!("--git-dir={}", git_dir.path().display()))
+        .arg(format!("--work-tree={}", dir.display()))
+        .args(args);
+      run(command)
+    };
+
+    git(&["init", "-q"])?;
+    git(&["add", "-A"])?;
+    git(&["commit", "-q", "-m", "Deploy"])?;
+    git(&["push", "-q", "--force", &self.remote, &format!("HEAD:refs/heads/{}", self.branch)])
+  }
+}
+
+// A plain directory, e.g. one served by a local web server. Its previous
+// contents are replaced.
+#[derive(Debug, Default, PartialEq)]
+pub struct Local {
+  pub dir: PathBuf,
+}
+
+impl Deployer for Local {
+  fn name(&self) -> String {
+    self.dir.display().to_string()
+  }
+
+  fn deploy(&self, dir: &Path) -> Result<(), String> {
+    io::try_copy_dir(dir, &self.dir)
+  }
+}
+
+// Runs command, failing when it can't be started or exits unsuccessfully
+fn run(mut command: Command) -> Result<(), String> {
+  let program = command.get_program().to_string_lossy().into_owned();
+  let status =
+    command
+    .status()
+    .map_err(|e| format!("Failed to execute {program}: {e}"))?;
+
+  if status.success() {
+    Ok(())
+  } else {
+    Err(format!("{program} failed with status: {status}"))
+  }
+}
@@ -0,0 +1,720 @@
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::check::Finding;
+use crate::markdown::{self, Line};
+use crate::string::{escape_html, hex};
+
+// Fence languages rendered as diagrams
+pub const KINDS: [&str; 2] = ["dot", "sequence"];
+
+// Sizes in pixels
+const FONT_WIDTH: f64 = 8.0;
+const NODE_HEIGHT: f64 = 36.0;
+const NODE_GAP: f64 = 30.0;
+const RANK_GAP: f64 = 60.0;
+const MESSAGE_GAP: f64 = 40.0;
+const MARGIN: f64 = 10.0;
+
+// Replaces ```dot and ```sequence fences with an inline SVG <figure>. SVGs
+// are cached in cache by a hash of their source so unchanged diagrams aren't
+// laid out again. Diagrams that don't parse are left as code for errors to
+// report. Repeats of a diagram get ids of their own.
+pub fn from_markdown(markdown: &str, cache: &Path) -> String {
+  let mut output = String::new();
+  let mut last = 0;
+  let mut repeats: HashMap<String, usize> = HashMap::new();
+  for fence in fences(markdown) {
+    output.push_str(&markdown[last..fence.start]);
+    match cached(&fence.kind, &fence.source, cache) {
+      Ok(svg) => {
+        let id = id(&fence.kind, &fence.source);
+        let repeat = repeats.entry(id.clone()).or_default();
+        let svg =
+          if *repeat == 0 {
+            svg
+          } else {
+            svg.replace(&format!("arrow-{id}"), &format!("arrow-{id}-{repeat}"))
+          };
+        *repeat += 1;
+        write!(output, "<figure class=\"diagram\">\n{svg}\n</figure>\n").unwrap();
+      },
+      Err(_) => output.push_str(&markdown[fence.start..fence.end]),
+    }
+    last = fence.end;
+  }
+  output.push_str(&markdown[last..]);
+  output
+}
+
+// Diagrams in the Markdown files that don't parse, located by line
+pub fn errors(files: &[PathBuf]) -> Vec<Finding> {
+  let mut findings = vec![];
+
+  for file in files {
+    let Ok(contents) = fs::read_to_string(file) else { continue };
+    for fence in fences(&contents) {
+      if let Err((line, message)) = render(&fence.kind, &fence.source) {
+        findings.push(Finding {
+          file: file.to_str().unwrap().to_string(),
+          // The source starts on the line after the opening fence
+          line: contents[..fence.start].matches('\n').count() + 1 + line,
+          message: format!("{} diagram: {message}", fence.kind),
+        });
+      }
+    }
+  }
+
+  findings
+}
+
+// SVG for a diagram's source, or the line within it and reason it failed
+pub fn render(kind: &str, source: &str) -> Result<String, (usize, String)> {
+  let id = id(kind, source);
+  match kind {
+    "dot" => Ok(dot(&parse_dot(source)?, &id)),
+    "sequence" => Ok(sequence(&parse_sequence(source)?, &id)),
+    _ => Err((0, format!("unknown kind {kind}"))),
+  }
+}
+
+// Keeps the ids in a diagram apart from other diagrams on the page
+fn id(kind: &str, source: &str) -> String {
+  hex(&Sha256::digest(format!("{kind}\n{source}").as_bytes()))[..12].to_string()
+}
+
+fn cached(kind: &str, source: &str, cache: &Path) -> Result<String, (usize, String)> {
+  let hash = hex(&Sha256::digest(format!("{kind}\n{source}").as_bytes()));
+  let path = cache.join(format!("{}.svg", &hash[..16]));
+  if let Ok(svg) = fs::read_to_string(&path) {
+    return Ok(svg);
+  }
+
+  let svg = render(kind, source)?;
+  fs::create_dir_all(cache).unwrap();
+  fs::write(&path, &svg).unwrap();
+  Ok(svg)
+}
+
+struct Fence {
+  kind: String,
+  source: String,
+  // Byte range of the whole fence including its markers
+  start: usize,
+  end: usize,
+}
+
+// Diagram fences, skipping over any other fenced code. Unclosed fences
+// aren't diagrams.
+fn fences(markdown: &str) -> Vec<Fence> {
+  let mut found = vec![];
+  let mut open: Option<(&str, usize)> = None;
+  let mut source = String::new();
+  let mut offset = 0;
+
+  for (kind, line) in markdown::lines(markdown) {
+    match (kind, open) {
+      (Line::Open, _) => {
+        let trimmed = line.trim();
+        open = Some((trimmed.trim_start_matches(['`', '~']).trim(), offset));
+      },
+      (Line::Code, _) => source.push_str(line),
+      (Line::Close, Some((kind, start))) => {
+        if KINDS.contains(&kind) {
+          let end = offset + line.len();
+          found.push(Fence { kind: kind.to_string(), source: source.clone(), start, end });
+        }
+        open = None;
+        source.clear();
+      },
+      _ => (),
+    }
+    offset += line.len();
+  }
+
+  found
+}
+
+fn px(n: usize) -> f64 {
+  f64::from(u32::try_from(n).unwrap())
+}
+
+fn text_width(label: &str) -> f64 {
+  px(label.chars().count()) * FONT_WIDTH
+}
+
+fn svg_open(width: f64, height: f64, label: &str, id: &str) -> String {
+  format!(
+    "<svg class=\"diagram\" xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {width:.0} {height:.0}\" \
+     width=\"{width:.0}\" height=\"{height:.0}\" role=\"img\" aria-label=\"{}\">\n\
+     <defs><marker id=\"arrow-{id}\" viewBox=\"0 0 10 10\" refX=\"10\" refY=\"5\" markerWidth=\"8\" \
+     markerHeight=\"8\" orient=\"auto-start-reverse\"><path class=\"arrowhead\" d=\"M0,0 L10,5 L0,10 z\"/>\
+     </marker></defs>\n",
+    escape_html(label),
+  )
+}
+
+fn svg_text(x: f64, y: f64, class: &str, label: &str) -> String {
+  format!(
+    "<text class=\"{class}\" x=\"{x:.1}\" y=\"{y:.1}\" text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>\n",
+    escape_html(label),
+  )
+}
+
+// GRAPHVIZ
+
+#[derive(Debug, Default)]
+struct Graph {
+  directed: bool,
+  // Left to right rather than top to bottom
+  horizontal: bool,
+  label: String,
+  nodes: Vec<Node>,
+  edges: Vec<Edge>,
+}
+
+#[derive(Debug)]
+struct Node {
+  id: String,
+  label: String,
+  shape: String,
+}
+
+#[derive(Debug)]
+struct Edge {
+  from: usize,
+  to: usize,
+  label: String,
+}
+
+impl Graph {
+  fn node(&mut self, id: &str) -> usize {
+    if let Some(i) = self.nodes.iter().position(|n| n.id == id) {
+      return i;
+    }
+    self.nodes.push(Node { id: id.to_string(), label: id.to_string(), shape: String::new() });
+    self.nodes.len() - 1
+  }
+}
+
+// Tokens of the DOT language with the line they're on
+fn tokenize(source: &str) -> Result<Vec<(String, usize)>, (usize, String)> {
+  let mut tokens = vec![];
+  let chars: Vec<char> = source.chars().collect();
+  let mut line = 1;
+  let mut i = 0;
+
+  while i < chars.len() {
+    let c = chars[i];
+    match c {
+      '\n' => { line += 1; i += 1; },
+      c if c.is_whitespace() => i += 1,
+      '#' => while i < chars.len() && chars[i] != '\n' { i += 1 },
+      '/' if chars.get(i + 1) == Some(&'/') => while i < chars.len() && chars[i] != '\n' { i += 1 },
+      '/' if chars.get(i + 1) == Some(&'*') => {
+        i += 2;
+        while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+          if chars[i] == '\n' { line += 1; }
+          i += 1;
+        }
+        i += 2;
+      },
+      '{' | '}' | '[' | ']' | ';' | ',' | '=' => {
+        tokens.push((c.to_string(), line));
+        i += 1;
+      },
+      '-' if matches!(chars.get(i + 1), Some('>' | '-')) => {
+        tokens.push((chars[i..i + 2].iter().collect(), line));
+        i += 2;
+      },
+      '"' => {
+        let mut text = String::new();
+        i += 1;
+        while i < chars.len() && chars[i] != '"' {
+          if chars[i] == '\\' && chars.get(i + 1) == Some(&'"') { i += 1; }
+          if chars[i] == '\n' { line += 1; }
+          text.push(chars[i]);
+          i += 1;
+        }
+        if i == chars.len() { return Err((line, "unterminated string".to_string())); }
+        // Quoted so "->" and friends aren't mistaken for syntax
+        tokens.push((format!("\"{text}"), line));
+        i += 1;
+      },
+      c if c.is_alphanumeric() || c == '_' || c == '.' => {
+        let start = i;
+        while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '.') {
+          i += 1;
+        }
+        tokens.push((chars[start..i].iter().collect(), line));
+      },
+      c => return Err((line, format!("unexpected {c}"))),
+    }
+  }
+
+  Ok(tokens)
+}
+
+// The Graphviz subset: graph and digraph with node and edge statements,
+// label and shape attributes, node defaults and rankdir
+fn parse_dot(source: &str) -> Result<Graph, (usize, String)> {
+  let tokens = tokenize(source)?;
+  let mut graph = Graph::default();
+  let mut i = 0;
+  let last_line = tokens.last().map_or(1, |(_, line)| *line);
+  let at = |i: usize| tokens.get(i).map_or(("", last_line), |(t, line)| (t.as_str(), *line));
+  let id = |token: &str| token.strip_prefix('"').unwrap_or(token).to_string();
+  let is_id = |token: &str| {
+    token.starts_with('"') || token.chars().next().is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '.')
+  };
+
+  if at(i).0 == "strict" { i += 1; }
+  graph.directed =
+    match at(i).0 {
+      "digraph" => true,
+      "graph" => false,
+      other => return Err((at(i).1, format!("expected graph or digraph, found {other:?}"))),
+    };
+  i += 1;
+  if at(i).0 != "{" && is_id(at(i).0) {
+    graph.label = id(at(i).0);
+    i += 1;
+  }
+  if at(i).0 != "{" { return Err((at(i).1, "expected {".to_string())); }
+  i += 1;
+
+  let mut default_shape = String::new();
+  loop {
+    let (token, line) = at(i);
+    match token {
+      "" => return Err((line, "missing }".to_string())),
+      "}" => break,
+      ";" | "," => { i += 1; continue; },
+      "subgraph" => return Err((line, "subgraphs aren't supported".to_string())),
+      _ => (),
+    }
+
+    if matches!(token, "graph" | "node" | "edge") && at(i + 1).0 == "[" {
+      let (attributes, next) = parse_attributes(&tokens, i + 1)?;
+      i = next;
+      for (key, value) in attributes {
+        match (token, key.as_str()) {
+          ("node", "shape") => default_shape = value,
+          ("graph", "rankdir") => graph.horizontal = value == "LR" || value == "RL",
+          ("graph", "label") => graph.label = value,
+          _ => (),
+        }
+      }
+      continue;
+    }
+    if !is_id(token) {
+      return Err((line, format!("unexpected {}", id(token))));
+    }
+    if at(i + 1).0 == "=" {
+      let value = id(at(i + 2).0);
+      match token {
+        "rankdir" => graph.horizontal = value == "LR" || value == "RL",
+        "label" => graph.label = value,
+        _ => (),
+      }
+      i += 3;
+      continue;
+    }
+
+    // a -> b -> c [attributes]
+    let mut chain = vec![graph.node(&id(token))];
+    i += 1;
+    while matches!(at(i).0, "->" | "--") {
+      let (arrow, line) = at(i);
+      if (arrow == "->") != graph.directed {
+        return Err((line, format!("{arrow} in a {}", if graph.directed { "digraph" } else { "graph" })));
+      }
+      if !is_id(at(i + 1).0) { return Err((line, format!("missing node after {arrow}"))); }
+      chain.push(graph.node(&id(at(i + 1).0)));
+      i += 2;
+    }
+    let attributes =
+      if at(i).0 == "[" {
+        let (attributes, next) = parse_attributes(&tokens, i)?;
+        i = next;
+        attributes
+      } else {
+        vec![]
+      };
+
+    let value = |key: &str| attributes.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone());
+    if chain.len() == 1 {
+      let node = &mut graph.nodes[chain[0]];
+      if let Some(label) = value("label") { node.label = label; }
+      if let Some(shape) = value("shape") { node.shape = shape; }
+    } else {
+      for pair in chain.windows(2) {
+        let label = value("label").unwrap_or_default();
+        graph.edges.push(Edge { from: pair[0], to: pair[1], label });
+      }
+    }
+  }
+
+  for node in &mut graph.nodes {
+    if node.shape.is_empty() { node.shape.clone_from(&default_shape); }
+  }
+  Ok(graph)
+}
+
+type Attributes = Vec<(String, String)>;
+
+// [key=value, ...] starting at the [, and the index after the ]
+fn parse_attributes(tokens: &[(String, usize)], start: usize) -> Result<(Attributes, usize), (usize, String)> {
+  let mut attributes = vec![];
+  let mut i = start + 1;
+  let id = |token: &str| token.strip_prefix('"').unwrap_or(token).to_string();
+
+  loop {
+    let Some((token, line)) = tokens.get(i) else {
+      return Err((tokens[start].1, "missing ]".to_string()));
+    };
+    match token.as_str() {
+      "]" => return Ok((attributes, i + 1)),
+      "," | ";" => i += 1,
+      _ => {
+        if tokens.get(i + 1).is_none_or(|(t, _)| t != "=") {
+          return Err((*line, format!("expected = after {}", id(token))));
+        }
+        let value = tokens.get(i + 2).map(|(t, _)| id(t)).unwrap_or_default();
+        attributes.push((id(token), value));
+        i += 3;
+      },
+    }
+  }
+}
+
+// Centre and size of each node and the size of the whole diagram
+struct Layout {
+  centres: Vec<(f64, f64)>,
+  sizes: Vec<(f64, f64)>,
+  width: f64,
+  height: f64,
+}
+
+// Edges back to a node still being visited, which close a cycle
+fn back_edges(graph: &Graph) -> Vec<bool> {
+  fn visit(node: usize, graph: &Graph, state: &mut [u8], back: &mut [bool]) {
+    state[node] = 1;
+    for (e, edge) in graph.edges.iter().enumerate().filter(|(_, e)| e.from == node) {
+      match state[edge.to] {
+        0 => visit(edge.to, graph, state, back),
+        1 => back[e] = true,
+        _ => (),
+      }
+    }
+    state[node] = 2;
+  }
+
+  let mut state = vec![0; graph.nodes.len()];
+  let mut back = vec![false; graph.edges.len()];
+  for node in 0..graph.nodes.len() {
+    if state[node] == 0 { visit(node, graph, &mut state, &mut back); }
+  }
+  back
+}
+
+// Nodes in ranks by their longest path from a source, ignoring edges that
+// close cycles, then ordered within their rank by the average position of
+// their predecessors
+fn layers(graph: &Graph) -> Vec<Vec<usize>> {
+  let count = graph.nodes.len();
+  let forward: Vec<&Edge> =
+    graph
+    .edges
+    .iter()
+    .zip(back_edges(graph))
+    .filter(|(edge, back)| !back && edge.from != edge.to)
+    .map(|(edge, _)| edge)
+    .collect();
+
+  let mut rank = vec![0; count];
+  // Relaxing until stable takes at most a round per node without cycles
+  for _ in 0..count {
+    let mut changed = false;
+    for edge in &forward {
+      if rank[edge.to] < rank[edge.from] + 1 {
+        rank[edge.to] = rank[edge.from] + 1;
+        changed = true;
+      }
+    }
+    if !changed { break; }
+  }
+
+  let mut layers: Vec<Vec<usize>> = vec![vec![]; rank.iter().max().map_or(0, |r| r + 1)];
+  for (node, rank) in rank.iter().enumerate() {
+    layers[*rank].push(node);
+  }
+  let mut position: HashMap<usize, usize> = HashMap::new();
+  for layer in &mut layers {
+    // Scaled by 100 to keep averages in whole numbers
+    let barycenter = |node: usize| {
+      let parents: Vec<usize> =
+        forward.iter().filter(|e| e.to == node).filter_map(|e| position.get(&e.from)).copied().collect();
+      if parents.is_empty() { usize::MAX } else { parents.iter().sum::<usize>() * 100 / parents.len() }
+    };
+    layer.sort_by_key(|node| barycenter(*node));
+    for (i, node) in layer.iter().enumerate() { position.insert(*node, i); }
+  }
+  layers
+}
+
+// Ranks run down the page, or across it for rankdir=LR
+fn layout(graph: &Graph) -> Layout {
+  let layers = layers(graph);
+  let sizes: Vec<(f64, f64)> =
+    graph
+    .nodes
+    .iter()
+    .map(|node| {
+      let width = (text_width(&node.label) + 24.0).max(60.0);
+      match node.shape.as_str() {
+        "circle" | "doublecircle" => (width, width),
+        "diamond" => (width * 1.5, NODE_HEIGHT * 1.5),
+        _ => (width, NODE_HEIGHT),
+      }
+    })
+    .collect();
+  let along = |node: usize| if graph.horizontal { sizes[node].1 } else { sizes[node].0 };
+  let across = |node: usize| if graph.horizontal { sizes[node].0 } else { sizes[node].1 };
+  let extents: Vec<f64> =
+    layers
+    .iter()
+    .map(|layer| {
+      layer.iter().map(|node| along(*node)).sum::<f64>() + NODE_GAP * px(layer.len().saturating_sub(1))
+    })
+    .collect();
+  let widest = extents.iter().copied().fold(0.0, f64::max);
+
+  let mut centres = vec![(0.0, 0.0); graph.nodes.len()];
+  let mut depth = MARGIN;
+  for (layer, extent) in layers.iter().zip(&extents) {
+    let thickness = layer.iter().map(|node| across(*node)).fold(0.0, f64::max);
+    let mut offset = MARGIN + (widest - extent) / 2.0;
+    for node in layer {
+      let (a, b) = (offset + along(*node) / 2.0, depth + thickness / 2.0);
+      centres[*node] = if graph.horizontal { (b, a) } else { (a, b) };
+      offset += along(*node) + NODE_GAP;
+    }
+    depth += thickness + RANK_GAP;
+  }
+
+  let (width, height) =
+    if layers.is_empty() {
+      (MARGIN * 2.0, MARGIN * 2.0)
+    } else if graph.horizontal {
+      (depth - RANK_GAP + MARGIN, widest + MARGIN * 2.0)
+    } else {
+      (widest + MARGIN * 2.0, depth - RANK_GAP + MARGIN)
+    };
+  Layout { centres, sizes, width, height }
+}
+
+fn dot(graph: &Graph, id: &str) -> String {
+  let layout = layout(graph);
+  let label = if graph.label.is_empty() { "Diagram" } else { &graph.label };
+  let mut svg = svg_open(layout.width, layout.height, label, id);
+  let marker = if graph.directed { format!(" marker-end=\"url(#arrow-{id})\"") } else { String::new() };
+
+  for edge in &graph.edges {
+    let (from, to) = (layout.centres[edge.from], layout.centres[edge.to]);
+    let label_at =
+      if edge.from == edge.to {
+        // A loop off the right hand side
+        let (width, height) = layout.sizes[edge.from];
+        let (x, y) = (from.0 + width / 2.0, from.1);
+        writeln!(
+          svg,
+          "<path class=\"edge\" d=\"M{:.1},{:.1} C{:.1},{:.1} {:.1},{:.1} {:.1},{:.1}\" fill=\"none\"{marker}/>",
+          x, y - height / 4.0, x + 30.0, y - height, x + 30.0, y + height, x, y + height / 4.0,
+        ).unwrap();
+        (x + 40.0, y)
+      } else {
+        let start = clip(from, layout.sizes[edge.from], &graph.nodes[edge.from].shape, to);
+        let end = clip(to, layout.sizes[edge.to], &graph.nodes[edge.to].shape, from);
+        writeln!(
+          svg,
+          "<line class=\"edge\" x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\"{marker}/>",
+          start.0, start.1, end.0, end.1,
+        ).unwrap();
+        (f64::midpoint(start.0, end.0) + 4.0, f64::midpoint(start.1, end.1))
+      };
+    if !edge.label.is_empty() {
+      svg.push_str(&svg_text(label_at.0, label_at.1, "edge-label", &edge.label));
+    }
+  }
+
+  for (node, (centre, size)) in graph.nodes.iter().zip(layout.centres.iter().zip(&layout.sizes)) {
+    svg.push_str(&shape(&node.shape, *centre, *size));
+    svg.push_str(&svg_text(centre.0, centre.1, "node-label", &node.label));
+  }
+
+  svg.push_str("</svg>");
+  svg
+}
+
+fn shape(shape: &str, (x, y): (f64, f64), (width, height): (f64, f64)) -> String {
+  let (half_width, half_height) = (width / 2.0, height / 2.0);
+  match shape {
+    "ellipse" | "oval" | "circle" | "doublecircle" => format!(
+      "<ellipse class=\"node\" cx=\"{x:.1}\" cy=\"{y:.1}\" rx=\"{half_width:.1}\" ry=\"{half_height:.1}\"/>\n",
+    ),
+    "diamond" => format!(
+      "<polygon class=\"node\" points=\"{:.1},{y:.1} {x:.1},{:.1} {:.1},{y:.1} {x:.1},{:.1}\"/>\n",
+      x - half_width, y - half_height, x + half_width, y + half_height,
+    ),
+    _ => format!(
+      "<rect class=\"node\" x=\"{:.1}\" y=\"{:.1}\" width=\"{width:.1}\" height=\"{height:.1}\" rx=\"4\"/>\n",
+      x - half_width, y - half_height,
+    ),
+  }
+}
+
+// Where the line from a node's centre towards a point leaves its shape
+fn clip(centre: (f64, f64), (w, h): (f64, f64), shape: &str, toward: (f64, f64)) -> (f64, f64) {
+  let (dx, dy) = (toward.0 - centre.0, toward.1 - centre.1);
+  if dx == 0.0 && dy == 0.0 { return centre; }
+  let (hw, hh) = (w / 2.0, h / 2.0);
+  let t =
+    match shape {
+      "ellipse" | "oval" | "circle" | "doublecircle" => 1.0 / ((dx / hw).powi(2) + (dy / hh).powi(2)).sqrt(),
+      "diamond" => 1.0 / (dx.abs() / hw + dy.abs() / hh),
+      _ => (hw / dx.abs()).min(hh / dy.abs()),
+    };
+  (centre.0 + dx * t, centre.1 + dy * t)
+}
+
+// SEQUENCE DIAGRAMS
+
+#[derive(Debug, Default)]
+struct Sequence {
+  participants: Vec<String>,
+  messages: Vec<Message>,
+}
+
+#[derive(Debug)]
+struct Message {
+  from: usize,
+  to: usize,
+  text: String,
+  // --> replies are drawn dashed
+  reply: bool,
+}
+
+impl Sequence {
+  fn participant(&mut self, name: &str) -> usize {
+    if let Some(i) = self.participants.iter().position(|p| p == name) {
+      return i;
+    }
+    self.participants.push(name.to_string());
+    self.participants.len() - 1
+  }
+}
+
+// One statement per line: `participant Name` to fix the order of the columns,
+// `A -> B: message` and `A --> B: reply`. # starts a comment.
+fn parse_sequence(source: &str) -> Result<Sequence, (usize, String)> {
+  let mut sequence = Sequence::default();
+
+  for (i, line) in source.lines().enumerate() {
+    let line = line.split('#').next().unwrap().trim();
+    if line.is_empty() { continue; }
+
+    if let Some(name) = line.strip_prefix("participant ") {
+      sequence.participant(name.trim());
+      continue;
+    }
+
+    let (arrow, reply) = if line.contains("-->") { ("-->", true) } else { ("->", false) };
+    let Some((from, rest)) = line.split_once(arrow) else {
+      return Err((i + 1, format!("expected A -> B: message, found {line:?}")));
+    };
+    let (to, text) = rest.split_once(':').unwrap_or((rest, ""));
+    let (from, to) = (from.trim(), to.trim());
+    if from.is_empty() || to.is_empty() {
+      return Err((i + 1, format!("missing participant in {line:?}")));
+    }
+    let (from, to) = (sequence.participant(from), sequence.participant(to));
+    sequence.messages.push(Message { from, to, text: text.trim().to_string(), reply });
+  }
+
+  Ok(sequence)
+}
+
+// Participants in boxes along the top with lifelines down from them and
+// messages as arrows between the lifelines in order
+fn sequence(sequence: &Sequence, id: &str) -> String {
+  let column_width = |i: usize| {
+    let name = text_width(&sequence.participants[i]) + 24.0;
+    let messages =
+      sequence
+      .messages
+      .iter()
+      .filter(|m| m.from.min(m.to) == i)
+      .map(|m| text_width(&m.text) + 24.0)
+      .fold(0.0, f64::max);
+    name.max(messages).max(100.0)
+  };
+  let mut columns = vec![];
+  let mut x = MARGIN;
+  for i in 0..sequence.participants.len() {
+    let width = column_width(i);
+    let box_width = (text_width(&sequence.participants[i]) + 24.0).max(60.0);
+    columns.push((x + box_width / 2.0, box_width));
+    x += width.max(box_width) + NODE_GAP;
+  }
+  let width = (x - NODE_GAP + MARGIN).max(MARGIN * 2.0);
+  let top = MARGIN + NODE_HEIGHT;
+  let height = top + MESSAGE_GAP * px(sequence.messages.len() + 1) + MARGIN;
+
+  let mut svg = svg_open(width, height, "Sequence diagram", id);
+  for (name, (x, box_width)) in sequence.participants.iter().zip(&columns) {
+    writeln!(
+      svg,
+      "<line class=\"lifeline\" x1=\"{x:.1}\" y1=\"{top:.1}\" x2=\"{x:.1}\" y2=\"{:.1}\"/>",
+      height - MARGIN,
+    ).unwrap();
+    writeln!(
+      svg,
+      "<rect class=\"node\" x=\"{:.1}\" y=\"{MARGIN:.1}\" width=\"{box_width:.1}\" height=\"{NODE_HEIGHT:.1}\" rx=\"4\"/>",
+      x - box_width / 2.0,
+    ).unwrap();
+    svg.push_str(&svg_text(*x, MARGIN + NODE_HEIGHT / 2.0, "node-label", name));
+  }
+
+  let marker = format!(" marker-end=\"url(#arrow-{id})\"");
+  for (i, message) in sequence.messages.iter().enumerate() {
+    let y = top + MESSAGE_GAP * px(i + 1);
+    let class = if message.reply { "edge reply" } else { "edge" };
+    let (from, to) = (columns[message.from].0, columns[message.to].0);
+    if message.from == message.to {
+      writeln!(
+        svg,
+        "<path class=\"{class}\" d=\"M{from:.1},{:.1} h30 v16 h-30\" fill=\"none\"{marker}/>",
+        y - 8.0,
+      ).unwrap();
+      if !message.text.is_empty() {
+        let x = from + 36.0 + text_width(&message.text) / 2.0;
+        svg.push_str(&svg_text(x, y, "edge-label", &message.text));
+      }
+    } else {
+      writeln!(
+        svg,
+        "<line class=\"{class}\" x1=\"{from:.1}\" y1=\"{y:.1}\" x2=\"{to:.1}\" y2=\"{y:.1}\"{marker}/>",
+      ).unwrap();
+      if !message.text.is_empty() {
+        svg.push_str(&svg_text(f64::midpoint(from, to), y - 10.0, "edge-label", &message.text));
+      }
+    }
+  }
+
+  svg.push_str("</svg>");
+  svg
+}
@@ -0,0 +1,83 @@
+use std::fmt::Write;
+use std::fs;
+
+use crate::collection::Collection;
+use crate::config::SiteConfig;
+use crate::date;
+use crate::index::{self, Sort};
+use crate::post::{self, Metadata, PathConfig};
+use crate::seo;
+use crate::string::escape_html;
+
+// Most entries in a feed, readers only look for new ones
+const MAX_ENTRIES: usize = 20;
+
+// Writes an Atom feed of a collection's published items to feed.xml in its
+// directory, most recent first by the collection's sort
+pub fn generate(items: &[Metadata], collection: &Collection, paths: &PathConfig, site: &SiteConfig) {
+  if !collection.feed { return; }
+
+  fs::create_dir_all(&paths.public_posts).unwrap();
+  fs::write(paths.public_posts.join("feed.xml"), atom(items, collection, site)).unwrap();
+}
+
+pub fn atom(items: &[Metadata], collection: &Collection, site: &SiteConfig) -> String {
+  let base = site.base_url.trim_end_matches('/');
+  let mut entries: Vec<&Metadata> = items.iter().filter(|p| p.visibility().is_public()).collect();
+  if collection.sort == Sort::Updated {
+    entries.sort_by(|a, b| index::last_changed(b).cmp(index::last_changed(a)));
+  }
+  entries.truncate(MAX_ENTRIES);
+
+  let latest = entries.iter().map(|p| updated(p)).max().unwrap_or_else(date::today);
+  let mut xml = String::new();
+  writeln!(xml, "<?xml version=\"1.0\" encoding=\"utf-8\"?>").unwrap();
+  writeln!(xml, "<feed xmlns=\"http://www.w3.org/2005/Atom\">").unwrap();
+  writeln!(xml, "  <title>{}</title>", escape_html(&title(collection, site))).unwrap();
+  writeln!(xml, "  <link href=\"{base}{}\" rel=\"self\"/>", collection.feed_url()).unwrap();
+  writeln!(xml, "  <link href=\"{base}{}\"/>", collection.url()).unwrap();
+  writeln!(xml, "  <id>{base}{}</id>", collection.url()).unwrap();
+  writeln!(xml, "  <updated>{latest}T00:00:00Z</updated>").unwrap();
+  writeln!(xml, "  <author><name>{}</name></author>", escape_html(&site.author)).unwrap();
+
+  for p in entries {
+    let url = escape_html(&format!("{base}{}", p.url()));
+    writeln!(xml, "  <entry>").unwrap();
+    writeln!(xml, "    <title>{}</title>", escape_html(&p.title)).unwrap();
+    writeln!(xml, "    <link href=\"{url}\"/>").unwrap();
+    writeln!(xml, "    <id>{url}</id>").unwrap();
+    if post::is_date(&p.created) {
+      writeln!(xml, "    <published>{}T00:00:00Z</published>", p.created).unwrap();
+    }
+    writeln!(xml, "    <updated>{}T00:00:00Z</updated>", updated(p)).unwrap();
+    writeln!(xml, "    <summary>{}</summary>", escape_html(&seo::description(p))).unwrap();
+    for tag in p.tags.split_whitespace() {
+      writeln!(xml, "    <category term=\"{}\"/>", escape_html(tag)).unwrap();
+    }
+    writeln!(xml, "  </entry>").unwrap();
+  }
+
+  xml.push_str("</feed>\n");
+  xml
+}
+
+// The <link> advertising a collection's feed in a page's <head>
+pub fn link(collection: &Collection, site: &SiteConfig) -> String {
+  if !collection.feed { return String::new(); }
+
+  format!(
+    "<link rel=\"alternate\" type=\"application/atom+xml\" title=\"{}\" href=\"{}\">\n",
+    escape_html(&title(collection, site)),
+    collection.feed_url(),
+  )
+}
+
+fn title(collection: &Collection, site: &SiteConfig) -> String {
+  format!("{} - {}", site.name, collection.title)
+}
+
+// Undated items count as changed today
+fn updated(p: &Metadata) -> String {
+  let changed = index::last_changed(p);
+  if post::is_date(changed) { changed.to_string() } else { date::today() }
+}
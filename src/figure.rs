@@ -0,0 +1,49 @@
+use regex::Regex;
+
+use crate::string::escape_html;
+
+// Converts standalone image paragraphs into <figure>s with a <figcaption>.
+// The image title becomes the caption, falling back to the alt text as
+// MultiMarkdown does. Images mixed in with other text are left alone.
+pub fn from_markdown(markdown: &str, link: bool) -> String {
+  let image_re =
+    Regex::new(r#"^!\[([^\]]*)\]\(\s*(\S+?)(?:\s+"([^"]*)")?\s*\)$"#).unwrap();
+  let lines: Vec<&str> = markdown.split('\n').collect();
+  let blank = |i: Option<usize>| {
+    i.and_then(|i| lines.get(i)).is_none_or(|l| l.trim().is_empty())
+  };
+
+  lines
+    .iter()
+    .enumerate()
+    .map(|(i, line)| {
+      let standalone = blank(i.checked_sub(1)) && blank(Some(i + 1));
+      match image_re.captures(line.trim_end()) {
+        Some(caps) if standalone => {
+          let title = caps.get(3).map_or("", |m| m.as_str());
+          figure(&caps[2], &caps[1], title, link)
+        },
+        _ => (*line).to_string(),
+      }
+    })
+    .collect::<Vec<String>>()
+    .join("\n")
+}
+
+fn figure(src: &str, alt: &str, title: &str, link: bool) -> String {
+  let src = escape_html(src);
+  let caption = if title.is_empty() { alt } else { title };
+  let mut img = format!("<img src=\"{src}\" alt=\"{}\" />", escape_html(alt));
+  if link {
+    img = format!("<a href=\"{src}\">{img}</a>");
+  }
+
+  if caption.is_empty() {
+    format!("<figure>\n{img}\n</figure>")
+  } else {
+    format!(
+      "<figure>\n{img}\n<figcaption>{}</figcaption>\n</figure>",
+      escape_html(caption),
+    )
+  }
+}
@@ -0,0 +1,147 @@
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use crate::assets::FINGERPRINTED;
+use crate::config::SiteConfig;
+use crate::html;
+use crate::io;
+use crate::string::base64;
+
+// Sources the pages load from, gathered from the generated HTML
+#[derive(Debug, Default, PartialEq)]
+pub struct Sources {
+  // Hashes of inline scripts and styles
+  pub scripts: BTreeSet<String>,
+  pub styles: BTreeSet<String>,
+  // Origins of anything loaded from another site
+  pub script_origins: BTreeSet<String>,
+  pub style_origins: BTreeSet<String>,
+  pub image_origins: BTreeSet<String>,
+  pub frame_origins: BTreeSet<String>,
+  // style="" attributes can't be hashed so allow inline styles instead
+  pub style_attributes: bool,
+}
+
+// Writes a Cloudflare Pages _headers file to root: security headers plus a
+// short cache for everything, replaced by a long immutable one for the
+// fingerprinted assets. Run it over the final output as the CSP hashes
+// inline scripts as they'll be served.
+pub fn generate(root: &Path, site: &SiteConfig) {
+  let mut headers = String::from("/*\n");
+  writeln!(headers, "  Content-Security-Policy: {}", policy(&sources(root))).unwrap();
+  if !site.hsts.is_empty() {
+    writeln!(headers, "  Strict-Transport-Security: {}", site.hsts).unwrap();
+  }
+  if !site.referrer_policy.is_empty() {
+    writeln!(headers, "  Referrer-Policy: {}", site.referrer_policy).unwrap();
+  }
+  headers.push_str("  X-Content-Type-Options: nosniff\n");
+  writeln!(headers, "  Cache-Control: public, max-age={}", site.cache_max_age).unwrap();
+
+  for dir in FINGERPRINTED {
+    write!(
+      headers,
+      "\n/{dir}/*\n  ! Cache-Control\n  Cache-Control: public, max-age={}, immutable\n",
+      site.asset_cache_max_age,
+    ).unwrap();
+  }
+
+  fs::write(root.join("_headers"), headers).unwrap();
+}
+
+pub fn sources(root: &Path) -> Sources {
+  let mut sources = Sources::default();
+
+  for file in io::files_in_tree(root) {
+    if file.extension().is_none_or(|ext| ext != "html") { continue; }
+    let contents = fs::read_to_string(&file).unwrap();
+    let tags = html::tags(&contents);
+
+    for (i, tag) in tags.iter().enumerate().filter(|(_, t)| !t.closing) {
+      if tag.attribute("style").is_some() {
+        sources.style_attributes = true;
+      }
+
+      let source = tag.attribute("src").or(tag.attribute("href")).unwrap_or_default();
+      match tag.name.as_str() {
+        "script" if tag.attribute("src").is_some() => {
+          add_origin(&mut sources.script_origins, source);
+        },
+        // JSON-LD and other data blocks aren't run so don't need allowing
+        "script" if tag.attribute("type").is_none_or(|t| t.contains("javascript")) => {
+          sources.scripts.insert(hash(inline(&contents, &tags, i)));
+        },
+        "style" => {
+          sources.styles.insert(hash(inline(&contents, &tags, i)));
+        },
+        "link" if tag.attribute("rel").is_some_and(|rel| rel.contains("stylesheet")) => {
+          add_origin(&mut sources.style_origins, source);
+        },
+        "img" | "source" | "video" | "audio" => add_origin(&mut sources.image_origins, source),
+        "iframe" => add_origin(&mut sources.frame_origins, source),
+        _ => (),
+      }
+    }
+  }
+
+  sources
+}
+
+// A Content-Security-Policy allowing only what the pages use
+pub fn policy(sources: &Sources) -> String {
+  let list = |base: &[&str], extra: &BTreeSet<String>| {
+    base.iter().map(|s| (*s).to_string()).chain(extra.iter().cloned()).collect::<Vec<_>>().join(" ")
+  };
+  let scripts: BTreeSet<String> =
+    sources.script_origins.iter().chain(&sources.scripts).cloned().collect();
+  let styles: BTreeSet<String> =
+    if sources.style_attributes {
+      sources.style_origins.iter().cloned().chain(["'unsafe-inline'".to_string()]).collect()
+    } else {
+      sources.style_origins.iter().chain(&sources.styles).cloned().collect()
+    };
+
+  let mut directives = vec![
+    "default-src 'self'".to_string(),
+    format!("script-src {}", list(&["'self'"], &scripts)),
+    format!("style-src {}", list(&["'self'"], &styles)),
+    format!("img-src {}", list(&["'self'", "data:"], &sources.image_origins)),
+    "font-src 'self'".to_string(),
+    "connect-src 'self'".to_string(),
+  ];
+  if !sources.frame_origins.is_empty() {
+    directives.push(format!("frame-src {}", list(&[], &sources.frame_origins)));
+  }
+  directives.extend([
+    "object-src 'none'".to_string(),
+    "base-uri 'self'".to_string(),
+    "form-action 'self'".to_string(),
+    "frame-ancestors 'none'".to_string(),
+  ]);
+
+  directives.join("; ")
+}
+
+// Contents of the element opened by tags[i]
+fn inline<'a>(html: &'a str, tags: &[html::Tag], i: usize) -> &'a str {
+  let name = &tags[i].name;
+  let end = tags[i + 1..].iter().find(|t| t.closing && &t.name == name).map_or(html.len(), |t| t.start);
+  &html[tags[i].end..end]
+}
+
+fn hash(contents: &str) -> String {
+  format!("'sha256-{}'", base64(&Sha256::digest(contents.as_bytes())))
+}
+
+// Adds the scheme and host of absolute URLs, same site ones need nothing more
+fn add_origin(origins: &mut BTreeSet<String>, url: &str) {
+  let url = if let Some(rest) = url.strip_prefix("//") { format!("https://{rest}") } else { url.to_string() };
+  let Some((scheme, rest)) = url.split_once("://") else { return };
+  let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+  if !host.is_empty() {
+    origins.insert(format!("{scheme}://{host}"));
+  }
+}
@@ -0,0 +1,158 @@
+// Just enough HTML parsing for the checks run over generated pages
+
+#[derive(Debug, PartialEq)]
+pub struct Tag {
+  pub name: String,
+  pub closing: bool,
+  pub attributes: Vec<(String, String)>,
+  // 1 based line the tag starts on
+  pub line: usize,
+  // Byte offsets of the tag within the document
+  pub start: usize,
+  pub end: usize,
+}
+
+impl Tag {
+  pub fn attribute(&self, name: &str) -> Option<&str> {
+    self
+      .attributes
+      .iter()
+      .find(|(n, _)| n == name)
+      .map(|(_, v)| v.as_str())
+  }
+}
+
+// Opening and closing tags in document order. Comments are skipped as is
+// anything inside <script> and <style>.
+pub fn tags(html: &str) -> Vec<Tag> {
+  let mut tags = vec![];
+  let mut at = 0;
+  let (mut line, mut counted) = (1, 0);
+
+  while let Some(offset) = html[at..].find('<') {
+    let start = at + offset;
+    let rest = &html[start..];
+
+    if rest.starts_with("<!--") {
+      at = start + rest.find("-->").map_or(rest.len(), |i| i + 3);
+      continue;
+    }
+
+    let end = start + tag_end(rest);
+    line += html[counted..start].matches('\n').count();
+    counted = start;
+    let Some(tag) = parse(&html[start..end], start, end, line) else {
+      at = start + 1;
+      continue;
+    };
+    at = end;
+
+    if !tag.closing && (tag.name == "script" || tag.name == "style") {
+      let close = format!("</{}", tag.name);
+      at += html[at..].to_ascii_lowercase().find(&close).unwrap_or(html.len() - at);
+    }
+    tags.push(tag);
+  }
+
+  tags
+}
+
+// Text between the end of one tag and the start of another
+pub fn text_between(html: &str, from: &Tag, to: &Tag) -> String {
+  strip_tags(&html[from.end..to.start])
+}
+
+pub fn strip_tags(html: &str) -> String {
+  let mut text = String::new();
+  let mut in_tag = false;
+  for c in html.chars() {
+    match c {
+      '<' => in_tag = true,
+      '>' if in_tag => in_tag = false,
+      c if !in_tag => text.push(c),
+      _ => (),
+    }
+  }
+  decode(&text)
+}
+
+pub fn line_at(html: &str, offset: usize) -> usize {
+  html[..offset].matches('\n').count() + 1
+}
+
+// Decodes the handful of entities MultiMarkdown and the templates produce
+pub fn decode(text: &str) -> String {
+  text
+    .replace("&lt;", "<")
+    .replace("&gt;", ">")
+    .replace("&quot;", "\"")
+    .replace("&#39;", "'")
+    .replace("&amp;", "&")
+}
+
+fn tag_end(html: &str) -> usize {
+  let mut quote = None;
+  for (i, c) in html.char_indices() {
+    match (quote, c) {
+      (None, '"' | '\'') => quote = Some(c),
+      (Some(q), c) if q == c => quote = None,
+      (None, '>') => return i + 1,
+      _ => (),
+    }
+  }
+  html.len()
+}
+
+fn parse(tag: &str, start: usize, end: usize, line: usize) -> Option<Tag> {
+  let inner = tag.trim_start_matches('<').trim_end_matches('>').trim_end_matches('/');
+  let closing = inner.starts_with('/');
+  let inner = inner.trim_start_matches('/');
+  let name_end = inner.find(|c: char| c.is_whitespace()).unwrap_or(inner.len());
+  let name = inner[..name_end].to_lowercase();
+
+  let valid = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '!');
+  if !valid { return None; }
+
+  Some(Tag {
+    name,
+    closing,
+    attributes: attributes(&inner[name_end..]),
+    line,
+    start,
+    end,
+  })
+}
+
+fn attributes(source: &str) -> Vec<(String, String)> {
+  let mut attributes = vec![];
+  let mut chars = source.chars().peekable();
+
+  loop {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+    let name: String =
+      std::iter::from_fn(|| chars.next_if(|c| !c.is_whitespace() && *c != '='))
+      .collect();
+    if name.is_empty() {
+      if chars.next().is_none() { break; }
+      continue;
+    }
+
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+    let mut value = String::new();
+    if chars.next_if_eq(&'=').is_some() {
+      while chars.next_if(|c| c.is_whitespace()).is_some() {}
+      match chars.peek().copied() {
+        Some(quote @ ('"' | '\'')) => {
+          chars.next();
+          value = chars.by_ref().take_while(|c| *c != quote).collect();
+        },
+        _ => {
+          value = std::iter::from_fn(|| chars.next_if(|c| !c.is_whitespace())).collect();
+        },
+      }
+    }
+    attributes.push((name.to_lowercase(), decode(&value)));
+  }
+
+  attributes
+}
@@ -1,44 +1,178 @@
+use std::fmt::Write as _;
 use std::fs::File;
 use std::fs;
 use std::io::Write;
 
+use crate::config::SiteConfig;
+use crate::feed;
 use crate::io;
-use crate::post::{Metadata, PathConfig};
+use crate::post::{Metadata, PathConfig, Visibility};
 use crate::post;
-use crate::string::format_or_empty;
+use crate::seo;
+use crate::string::{escape_html, format_or_empty};
+use crate::task;
 
-// Generates the index.html page from metadata
-pub fn generate(posts: &[Metadata], paths: &PathConfig) {
-  let about = post::build(&paths.pages.join("about.md"));
-  let mut index = File::create(paths.public.join("index.html")).unwrap();
+// Order of the home page cards after any pinned posts
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Sort {
+  // Newest first by filename, as returned by post::build_all
+  #[default]
+  Created,
+  // Most recently updated (or created when never updated) first
+  Updated,
+}
 
+// Generates the index.html page from metadata, and page/2.html onwards when
+// there are more posts than fit on a page. Drafts and scheduled posts go at
+// the top of the first page without counting towards its size so removing
+// them leaves the pages as they'll be deployed. Expired posts are left out.
+// The first page also shows the progress of any devlog pages.
+pub fn generate(posts: &[Metadata], paths: &PathConfig, site: &SiteConfig) {
+  let about = post::build(&paths.pages.join("about.md"));
   let nav = io::load_template("nav");
-  let card = io::load_template("card");
-  let posts_html = posts.iter().map(|p| {
-    let created = format_or_empty("Published: ", &p.created);
-    let updated = format_or_empty("Updated: ", &p.updated);
-    let draft = if p.name.starts_with("draft-") { " draft" } else { "" };
-
-    card
-      .replace("{additional_classes}", draft)
-      .replace("{name}", &p.name)
-      .replace("{tags}", &p.tags)
-      .replace("{created}", &created)
-      .replace("{updated}", &updated)
-      .replace("{title}", &p.title)
-      .replace("{intro}", &p.intro)
-  }).collect::<Vec<String>>().join("\n");
-
+  let collection = site.posts();
+  let template = io::load_template(&collection.template);
   let more_html =
     format!("{} <div><a href=\"/about.html\">more...</a></div>", &about.intro);
-  let home =
-    fs::read_to_string("templates/home.html")
-    .unwrap()
-    .replace("{nav}", &nav)
-    .replace("{intro}", &more_html)
-    .replace("{posts}", &posts_html);
-
-  index.write_all(home.as_bytes()).unwrap();
+  let devlogs = devlogs(paths);
+
+  let (drafts, published): (Vec<&Metadata>, Vec<&Metadata>) =
+    posts
+    .iter()
+    .filter(|p| p.visibility() != Visibility::Expired)
+    .partition(|p| p.visibility().is_draft());
+  let published = order(published, collection.sort);
+  let per_page =
+    if site.posts_per_page == 0 { published.len().max(1) } else { site.posts_per_page };
+  let mut pages: Vec<&[&Metadata]> = published.chunks(per_page).collect();
+  if pages.is_empty() { pages.push(&[]); }
+
+  let page_dir = paths.public.join("page");
+  if page_dir.exists() {
+    fs::remove_dir_all(&page_dir).unwrap();
+  }
+
+  for (i, page) in pages.iter().enumerate() {
+    let number = i + 1;
+    let cards: Vec<&Metadata> =
+      if number == 1 {
+        drafts.iter().chain(page.iter()).copied().collect()
+      } else {
+        page.to_vec()
+      };
+    let posts_html = cards.into_iter().map(card).collect::<Vec<String>>().join("\n");
+
+    let mut head =
+      if number == 1 {
+        seo::home_head(&seo::description(&about), site) + &feed::link(&collection, site)
+      } else {
+        String::new()
+      };
+    let mut links = vec![];
+    if number > 1 {
+      writeln!(head, "<link rel=\"prev\" href=\"{}\">", page_url(number - 1)).unwrap();
+      links.push(format!("<a rel=\"prev\" href=\"{}\">← Newer</a>", page_url(number - 1)));
+    }
+    if number < pages.len() {
+      writeln!(head, "<link rel=\"next\" href=\"{}\">", page_url(number + 1)).unwrap();
+      links.push(format!("<a rel=\"next\" href=\"{}\">Older →</a>", page_url(number + 1)));
+    }
+    let pagination =
+      if links.is_empty() {
+        String::new()
+      } else {
+        let total = pages.len();
+        format!("<p class=\"pagination\">{} <span>Page {number} of {total}</span></p>", links.join(" "))
+      };
+
+    let home =
+      template
+      .replace("{head}", &head)
+      .replace("{nav}", &nav)
+      .replace("{intro}", &more_html)
+      .replace("{devlog}", if number == 1 { &devlogs } else { "" })
+      .replace("{pagination}", &pagination)
+      .replace("{posts}", &posts_html);
+
+    let path =
+      if number == 1 {
+        paths.public.join("index.html")
+      } else {
+        page_dir.join(format!("{number}.html"))
+      };
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    let mut index = File::create(path).unwrap();
+    index.write_all(home.as_bytes()).unwrap();
+  }
+}
+
+// Pinned posts first, then the rest in the given order
+pub fn order(mut posts: Vec<&Metadata>, sort: Sort) -> Vec<&Metadata> {
+  match sort {
+    Sort::Created => (),
+    Sort::Updated => posts.sort_by(|a, b| last_changed(b).cmp(last_changed(a))),
+  }
+  posts.sort_by_key(|p| !p.pinned);
+  posts
+}
+
+// The task list progress of each page in pages/ with category: devlog
+fn devlogs(paths: &PathConfig) -> String {
+  let mut pages = io::paths_in_dir(&paths.pages, &["md"]);
+  pages.reverse();
+  pages
+    .iter()
+    .map(|path| (post::build(path), path))
+    .filter(|(page, _)| page.category == "devlog")
+    .map(|(page, path)| {
+      let title = if page.title.is_empty() { &page.name } else { &page.title };
+      let progress = task::progress(&fs::read_to_string(path).unwrap());
+      format!(
+        "<p class=\"devlog\"><a href=\"/{}.html\">{}</a> {}</p>",
+        page.name,
+        escape_html(title),
+        progress.html(),
+      )
+    })
+    .collect::<Vec<String>>()
+    .join("\n")
+}
+
+pub fn page_url(number: usize) -> String {
+  if number == 1 { "/".to_string() } else { format!("/page/{number}.html") }
+}
+
+pub fn last_changed(p: &Metadata) -> &str {
+  if post::is_date(&p.updated) { &p.updated } else { &p.created }
+}
+
+// A post rendered with the card partial
+pub fn card(p: &Metadata) -> String {
+  let created = format_or_empty("Published: ", &p.created);
+  let updated = format_or_empty("Updated: ", &p.updated);
+  let draft =
+    match p.visibility() {
+      Visibility::Draft => " draft",
+      Visibility::Scheduled => " draft scheduled",
+      _ => "",
+    };
+  let pinned = if p.pinned { " pinned" } else { "" };
+  let part =
+    if p.part == 0 {
+      String::new()
+    } else {
+      format!("<p class=\"part\">Part {} of {}</p>", p.part, escape_html(&p.series))
+    };
+
+  io::load_template("card")
+    .replace("{additional_classes}", &format!("{draft}{pinned}"))
+    .replace("{url}", &p.url())
+    .replace("{tags}", &p.tags)
+    .replace("{created}", &created)
+    .replace("{updated}", &updated)
+    .replace("{title}", &p.title)
+    .replace("{part}", &part)
+    .replace("{intro}", &p.intro)
 }
 
 pub fn remove_drafts(paths: &PathConfig) {
@@ -47,7 +181,7 @@ pub fn remove_drafts(paths: &PathConfig) {
   let mut in_draft = false;
 
   for line in html.lines() {
-    if line.contains("<article class=\"card draft\">") {
+    if line.contains("<article class=\"card draft") {
       in_draft = true;
     }
 
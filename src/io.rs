@@ -1,7 +1,8 @@
 use std::path::{Path, PathBuf};
 use std::fs;
-use crate::path;
-use crate::post::PathConfig;
+use crate::permalink;
+use crate::post::{Metadata, PathConfig};
+use crate::release::Manifest;
 
 pub fn load_template(name: &str) -> String {
   fs::read_to_string(format!("templates/{name}.html")).unwrap()
@@ -22,19 +23,100 @@ pub fn paths_in_dir(path: &Path, extensions: &[&str]) -> Vec<PathBuf> {
   paths
 }
 
-pub fn remove_stale_html_posts(paths: &PathConfig) {
-  for html_file in paths_in_dir(&paths.public_posts, &["html"]) {
-    let md_file = path::markdown(&paths.artifacts, html_file.file_stem().unwrap().to_str().unwrap());
-    if !path::exists(&md_file) {
-      println!("  Removing {}", html_file.to_str().unwrap());
-      std::fs::remove_file(html_file).unwrap();
+// All files below path, recursively, sorted
+pub fn files_in_tree(path: &Path) -> Vec<PathBuf> {
+  let mut files = vec![];
+  if !path.is_dir() { return files; }
+
+  for entry in fs::read_dir(path).unwrap() {
+    let path = entry.unwrap().path();
+    if path.is_dir() {
+      files.extend(files_in_tree(&path));
+    } else {
+      files.push(path);
+    }
+  }
+  files.sort();
+  files
+}
+
+// Replaces `to` with a copy of `from`
+pub fn copy_dir(from: &Path, to: &Path) {
+  try_copy_dir(from, to).unwrap();
+}
+
+// copy_dir that reports the path it failed on instead of panicking
+pub fn try_copy_dir(from: &Path, to: &Path) -> Result<(), String> {
+  if to.exists() {
+    fs::remove_dir_all(to).map_err(failed(to))?;
+  }
+  fs::create_dir_all(to).map_err(failed(to))?;
+
+  for entry in fs::read_dir(from).map_err(failed(from))? {
+    let path = entry.map_err(failed(from))?.path();
+    let target = to.join(path.file_name().unwrap());
+    if path.is_dir() {
+      try_copy_dir(&path, &target)?;
+    } else {
+      fs::copy(&path, &target).map_err(failed(&path))?;
     }
   }
+  Ok(())
+}
+
+fn failed(path: &Path) -> impl Fn(std::io::Error) -> String + '_ {
+  move |e| format!("{}: {e}", path.display())
+}
+
+// Removes HTML under public/posts (or a collection's directory) that's
+// neither a post nor a redirect to one. Inbound links to what the last
+// deploy published will break unless it's added to the aliases of the post
+// it became, so those are warned about and returned. Drafts and scheduled
+// posts were never deployed, nothing links to them.
+pub fn remove_stale_html_posts(posts: &[Metadata], paths: &PathConfig, deployed: &Manifest) -> Vec<String> {
+  let mut gone = vec![];
+  let current: Vec<PathBuf> =
+    posts
+    .iter()
+    .flat_map(|p| {
+      let aliases = p.aliases.split_whitespace().map(|alias| permalink::output(alias, paths));
+      std::iter::once(p.output(paths)).chain(aliases)
+    })
+    .collect();
+
+  for html_file in files_in_tree(&paths.public_posts) {
+    let is_html = html_file.extension().is_some_and(|ext| ext == "html");
+    // The collection's listing
+    let is_listing = html_file == paths.public_posts.join("index.html");
+    if !is_html || is_listing || current.contains(&html_file) { continue; }
+
+    println!("  Removing {}", html_file.to_str().unwrap());
+    fs::remove_file(&html_file).unwrap();
+    remove_empty_dirs(html_file.parent().unwrap(), &paths.public_posts);
+
+    let relative = html_file.strip_prefix(&paths.public).unwrap().to_str().unwrap().replace('\\', "/");
+    if deployed.contains_key(&relative) {
+      let url = format!("/{}", relative.trim_end_matches("index.html"));
+      println!("  Warning: {url} is gone, add it to the aliases: of the post that replaced it");
+      gone.push(url);
+    }
+  }
+
+  gone
+}
+
+fn remove_empty_dirs(dir: &Path, root: &Path) {
+  let mut dir = dir.to_path_buf();
+  while dir != root && dir.starts_with(root) && fs::remove_dir(&dir).is_ok() {
+    dir.pop();
+  }
 }
 
-pub fn remove_drafts(paths: &PathConfig) {
-  for html_file in paths_in_dir(&paths.public_posts, &["html"]) {
-    if html_file.file_stem().unwrap().to_str().unwrap().starts_with("draft-") {
+// Removes the HTML of drafts and scheduled posts
+pub fn remove_drafts(posts: &[Metadata], paths: &PathConfig) {
+  for p in posts.iter().filter(|p| p.visibility().is_draft()) {
+    let html_file = p.output(paths);
+    if html_file.exists() {
       println!("  Removing draft {}", html_file.to_str().unwrap());
       std::fs::remove_file(html_file).unwrap();
     }
@@ -1,18 +1,56 @@
-use std::path::{PathBuf};
+use std::fs;
+use std::path::{Path, PathBuf};
 
-pub mod cloudflare;
+pub mod a11y;
+pub mod archive;
+pub mod assets;
+pub mod callout;
+pub mod check;
+pub mod collection;
+pub mod compress;
+pub mod config;
+pub mod date;
+pub mod deploy;
+pub mod diagram;
+pub mod feed;
+pub mod figure;
+pub mod headers;
+pub mod html;
 pub mod index;
 pub mod io;
 pub mod markdown;
+pub mod math;
+pub mod minify;
 pub mod path;
+pub mod permalink;
 pub mod post;
+pub mod related;
+pub mod release;
 pub mod rs2md;
+pub mod scaffold;
+pub mod search;
+pub mod seo;
+pub mod series;
+pub mod sitemap;
 pub mod string;
+pub mod task;
+pub mod wiki;
 
-use crate::post::PathConfig;
+use crate::collection::Collection;
+use crate::config::SiteConfig;
+use crate::deploy::{Cloudflare, Target};
+use crate::post::{Metadata, PathConfig};
+
+// Pages built from pages/
+const PAGES: [&str; 3] = ["about", "404", "devlog"];
+
+// What the last deploy published, compared against by the next
+const DEPLOY_MANIFEST: &str = "deploy-manifest.tsv";
 
 pub fn build() {
   let paths = setup_config();
+  let site = setup_site();
+  let collection = site.posts();
 
   // posts -> artifacts
   println!("## Converting .rs files to .md");
@@ -20,42 +58,295 @@ pub fn build() {
 
   // artifacts
   println!("## Populating post metadata");
-  let posts = post::build_all(&paths);
+  let mut posts = post::build_all(&paths);
+  permalink::apply(&mut posts, &collection.permalink);
+
+  // artifacts/*.md & pages/*.md
+  println!("## Checking wiki links, math and diagrams");
+  let mut sources = sources(&posts, &paths);
+  sources.extend(PAGES.iter().map(|page| paths.pages.join(format!("{page}.md"))));
+  check_sources(&posts, &sources);
 
   // artifacts -> public/posts
   println!("## Generating HTML posts");
-  markdown::to_html_posts(&posts, &paths);
+  markdown::to_html_posts(&posts, &paths, &site);
 
   // pages -> public
   println!("## Generating HTML pages (e.g. about, 404)");
-  markdown::to_html_pages(vec!["about", "404"], &paths);
+  markdown::to_html_pages(PAGES.to_vec(), &posts, &paths, &site);
+  let pages = post::build_pages(&PAGES, &paths);
 
   // public/posts
   println!("## Post-processing posts");
-  post::post_process(&posts, &paths);
+  post::post_process(&posts, &paths, &site);
 
   // public/posts
   println!("## Removing stale HTML posts");
-  io::remove_stale_html_posts(&paths);
+  let deployed = release::read(Path::new(DEPLOY_MANIFEST));
+  io::remove_stale_html_posts(&posts, &paths, &deployed);
+
+  // devlog/, notes/, ... -> public/devlog/, public/notes/, ...
+  let mut everything = posts.clone();
+  for other in site.collections.iter().filter(|c| c.name != collection.name) {
+    everything.extend(build_collection(other, &paths, &site, &deployed));
+  }
 
-  // public/index.html
+  // public/**/*.html & public/_redirects
+  println!("## Generating redirects");
+  permalink::redirects(&everything, &paths);
+
+  // public/index.html & public/page/*.html
   println!("## Generating index.html");
-  index::generate(&posts, &paths);
+  index::generate(&posts, &paths, &site);
+
+  // public/posts/feed.xml
+  println!("## Generating feed");
+  feed::generate(&posts, &collection, &paths, &site);
+
+  // public/archive.html & public/archive/*.html
+  println!("## Generating archive pages");
+  archive::generate(&posts, &paths);
+
+  // public/series/*.html
+  println!("## Generating series pages");
+  series::generate(&posts, &paths);
+
+  // public/search-index.json & public/search.html
+  println!("## Generating search index");
+  search::generate(&posts, &paths, true);
+
+  // public/sitemap.xml & public/robots.txt
+  println!("## Generating sitemap.xml and robots.txt");
+  sitemap::generate(&everything, &pages, &paths, &site);
+
+  // public/**/*.{gz,br}
+  if !site.precompress.is_empty() {
+    println!("## Precompressing");
+    compress::precompress(&paths.public, &site.precompress);
+  }
+}
+
+// A collection other than posts goes through the same steps from its own
+// directory, ending with its listing page and feed. Collections without a
+// source directory are skipped.
+fn build_collection(
+  collection: &Collection,
+  paths: &PathConfig,
+  site: &SiteConfig,
+  deployed: &release::Manifest,
+) -> Vec<Metadata> {
+  if !collection.source.is_dir() { return vec![]; }
+  let paths = collection.paths(paths);
+
+  // <collection> -> artifacts-<collection>
+  println!("## Building the {} collection", collection.name);
+  fs::create_dir_all(&paths.artifacts).unwrap();
+  markdown::from_rs_or_md_to_md(&paths);
+  let mut items = post::build_all(&paths);
+  permalink::apply(&mut items, &collection.permalink);
+  let (items, misplaced) = collection::place(items, collection, &paths);
+  print_findings(&misplaced);
+  check_sources(&items, &sources(&items, &paths));
+
+  // artifacts-<collection> -> public/<collection>
+  markdown::to_html_posts(&items, &paths, site);
+  post::post_process(&items, &paths, site);
+  io::remove_stale_html_posts(&items, &paths, deployed);
+
+  // public/<collection>/index.html & public/<collection>/feed.xml
+  collection::listing(&items, collection, &paths, site);
+  feed::generate(&items, collection, &paths, site);
+  items
+}
+
+// The generated Markdown of each post
+fn sources(posts: &[Metadata], paths: &PathConfig) -> Vec<PathBuf> {
+  posts.iter().map(|p| PathBuf::from(path::markdown(&paths.artifacts, &p.name))).collect()
+}
+
+// Reports wiki links, math and diagrams that don't work out. They're left as
+// they are in the output.
+fn check_sources(posts: &[Metadata], sources: &[PathBuf]) {
+  let mut problems = wiki::unresolved(posts, sources);
+  problems.extend(math::errors(sources));
+  problems.extend(diagram::errors(sources));
+  print_findings(&problems);
+}
+
+fn print_findings(findings: &[check::Finding]) {
+  for finding in findings {
+    println!("  {finding}");
+  }
+  if !findings.is_empty() {
+    println!("  {} problem(s), fix them before deploying", findings.len());
+  }
+}
+
+// Stages public/ in dist/, without drafts, and deploys it unless it's a dry
+// run, printing what changed since the last deploy. public/ isn't touched.
+// Refuses to when drafts, placeholders or
+// broken links made it into dist/ unless forced. Returns whether the deploy
+// succeeded, or for a dry run whether it would have.
+pub fn deploy(dry_run: bool, force: bool) -> bool {
+  let paths = setup_config();
+  let site = setup_site();
+
+  // public -> dist, public/ is left as built so drafts stay viewable locally
+  println!("## Staging public/ in dist/");
+  io::copy_dir(&paths.public, &paths.dist);
+  compress::remove(&paths.dist);
+  let staged = PathConfig {
+    posts: paths.posts.clone(),
+    pages: paths.pages.clone(),
+    artifacts: paths.artifacts.clone(),
+    public: paths.dist.clone(),
+    public_posts: paths.dist.join(paths.public_posts.strip_prefix(&paths.public).unwrap()),
+    dist: paths.dist.clone(),
+  };
+
+  // remove drafts and scheduled posts from dist/posts
+  let mut posts = post::build_all(&paths);
+  permalink::apply(&mut posts, &site.posts().permalink);
+  io::remove_drafts(&posts, &staged);
+
+  // and from dist/<collection>
+  let mut everything = posts.clone();
+  for collection in site.collections.iter().filter(|c| c.name != collection::POSTS) {
+    if !collection.source.is_dir() { continue; }
+    let paths = collection.paths(&paths);
+    let mut items = post::build_all(&paths);
+    permalink::apply(&mut items, &collection.permalink);
+    io::remove_drafts(&items, &collection.paths(&staged));
+    everything.extend(items);
+  }
+
+  // remove draft <article>s from dist/index.html
+  index::remove_drafts(&staged);
+
+  // rebuild dist/search-index.json without drafts
+  search::generate(&posts, &staged, false);
+
+  // dist/**/*.{html,css}
+  println!("## Minifying HTML and CSS");
+  minify::run(&paths.dist);
+
+  // dist/{css,images,fonts,highlightjs,js}
+  println!("## Fingerprinting assets");
+  assets::fingerprint(&paths.dist, &site.base_url);
+
+  // dist/_headers
+  println!("## Generating headers");
+  headers::generate(&paths.dist, &site);
+
+  // dist/**/*.{gz,br}
+  if site.deploy_precompressed {
+    println!("## Precompressing");
+    compress::precompress(&paths.dist, &site.precompress);
+  }
+
+  // dist vs the last deploy
+  println!("## Changes since the last deploy");
+  let manifest = release::manifest(&paths.dist);
+  let changes = release::diff(&release::read(Path::new(DEPLOY_MANIFEST)), &manifest);
+  for change in &changes {
+    println!("  {change}");
+  }
+  println!("  {} file(s) changed", changes.len());
+
+  // drafts, {placeholder}s and broken links in dist
+  println!("## Checking for drafts, placeholders and broken links");
+  let blockers = release::blockers(&paths.dist, &output_sources(&paths, &site), &everything);
+  for blocker in &blockers {
+    println!("  {blocker}");
+  }
+  println!("  {} problem(s)", blockers.len());
+
+  let deployed =
+    if dry_run {
+      println!("## Dry run, nothing deployed");
+      Ok(())
+    } else if !blockers.is_empty() && !force {
+      Err("problems found, fix them or deploy with --force".to_string())
+    } else {
+      // dist -> Cloudflare Pages, S3, ...
+      let target = site.deploy.deployer();
+      println!("## Deploying to {}", target.name());
+      target.deploy(&paths.dist)
+    };
+  match &deployed {
+    Ok(()) if !dry_run => release::write(Path::new(DEPLOY_MANIFEST), &manifest),
+    Ok(()) => (),
+    Err(e) => eprintln!("Deploy failed: {e}"),
+  }
+
+  deployed.is_ok() && (force || blockers.is_empty())
 }
 
-pub fn deploy() {
+// Checks the generated output for broken references, returning whether it's
+// clean
+pub fn check() -> bool {
   let paths = setup_config();
-  // remove drafts from artifacts and public/posts
-  io::remove_drafts(&paths);
+  let sources = output_sources(&paths, &setup_site());
 
-  // remove draft <article>s from public/index.html
-  index::remove_drafts(&paths);
+  // public/**/*.{html,css}
+  println!("## Checking links");
+  let findings = check::links(&paths.public, &sources);
+  for finding in &findings {
+    println!("  {finding}");
+  }
+  println!("  {} broken reference(s)", findings.len());
 
-  // deploy to Cloudflare Pages
-  cloudflare::deploy();
+  // public/**/*.html, public/css/main.css
+  println!("## Checking accessibility");
+  let problems = a11y::lint(&paths.public, &sources);
+  for problem in &problems {
+    println!("  {problem}");
+  }
+  println!("  {} accessibility problem(s)", problems.len());
 
-  // Call build to restore drafts
-  build();
+  findings.is_empty() && problems.is_empty()
+}
+
+// The post, item or page each generated HTML file came from, so findings
+// point at what to fix
+fn output_sources(paths: &PathConfig, site: &SiteConfig) -> check::Sources {
+  let mut sources = check::Sources::new();
+
+  for collection in &site.collections {
+    let paths = collection.paths(paths);
+    if !paths.artifacts.is_dir() { continue; }
+    let mut items = post::build_all(&paths);
+    permalink::apply(&mut items, &collection.permalink);
+    sources.extend(check::sources(&items, &paths));
+  }
+
+  sources
+}
+
+// Creates a draft post, returning whether it could
+pub fn new_post(title: &str, tags: &[String], rs: bool) -> bool {
+  let paths = setup_config();
+  report(scaffold::new(&paths.posts, title, tags, rs), "Created")
+}
+
+// Publishes a draft post as of today, returning whether it could
+pub fn publish(name: &str) -> bool {
+  let paths = setup_config();
+  let site = setup_site();
+  report(scaffold::publish(&paths, name, &date::today(), &site.posts().permalink), "Published")
+}
+
+fn report(result: Result<PathBuf, String>, done: &str) -> bool {
+  match result {
+    Ok(path) => {
+      println!("{done} {}", path.display());
+      true
+    },
+    Err(e) => {
+      eprintln!("{e}");
+      false
+    },
+  }
 }
 
 fn setup_config() -> PathConfig {
@@ -65,6 +356,30 @@ fn setup_config() -> PathConfig {
     artifacts: PathBuf::from("artifacts"),
     public: PathBuf::from("public"),
     public_posts: PathBuf::from("public/posts"),
+    dist: PathBuf::from("dist"),
   }
 }
 
+fn setup_site() -> SiteConfig {
+  SiteConfig {
+    base_url: "https://electricvisions.com".to_string(),
+    name: "Electric Visions".to_string(),
+    author: "Phil Thompson".to_string(),
+    // Others, e.g. a devlog, go here once they have a directory of items
+    collections: vec![Collection::default()],
+    posts_per_page: 12,
+    related_posts: 3,
+    link_figures: true,
+    // Pages may change after a deploy, assets get a new name when they do
+    cache_max_age: 600,
+    asset_cache_max_age: 31_536_000,
+    hsts: "max-age=31536000; includeSubDomains".to_string(),
+    referrer_policy: "strict-origin-when-cross-origin".to_string(),
+    // Opt in with e.g. vec![Encoding::Gzip, Encoding::Brotli], needs gzip
+    // and brotli installed
+    precompress: vec![],
+    // Cloudflare Pages compresses on the fly
+    deploy_precompressed: false,
+    deploy: Target::Cloudflare(Cloudflare { project: "electricvisions".to_string() }),
+  }
+}
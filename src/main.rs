@@ -4,17 +4,56 @@
 // 3. Generates HTML from markdown in pages/ and writes to public/
 // 4. Removes stale HTML posts from public/posts/
 // 5. Post-processes HTML posts by inserting created & updated dates
-// 6. Generates HTML index from posts
+// 6. Builds any other collections in SiteConfig (e.g. devlog/) the same way
+//    into public/<collection>/ with a listing page and feed each
+// 7. Generates HTML index and feed from posts
 // metadata in md files overrides created date in filename
 // First H1 becomes title or it uses the metadata title if no H1
+//
+// `--deploy` publishes to the target in SiteConfig and exits non-zero when
+// that fails. `--dry-run` only lists what would change, and `--force` deploys
+// even with drafts, placeholders or broken links in the output
+//
+// `check` checks the generated output for broken links and accessibility
+// problems and exits non-zero when there are any
+//
+// `new <title> [tag...] [--rs]` creates posts/draft-<slug>.md (or .rs) and
+// `publish <draft>` renames a draft to today's date
 
 use std::env;
+use std::process;
 
 fn main() {
   let args: Vec<String> = env::args().collect();
 
   if args.iter().any(|a| a == "--deploy") {
-    website::deploy();
+    let dry_run = args.iter().any(|a| a == "--dry-run");
+    let force = args.iter().any(|a| a == "--force");
+    if !website::deploy(dry_run, force) {
+      process::exit(1);
+    }
+  } else if args.get(1).is_some_and(|a| a == "new") {
+    let rs = args.iter().any(|a| a == "--rs");
+    let words: Vec<String> = args.iter().skip(2).filter(|a| *a != "--rs").cloned().collect();
+    let Some((title, tags)) = words.split_first() else {
+      eprintln!("Usage: new <title> [tag...] [--rs]");
+      process::exit(1);
+    };
+    if !website::new_post(title, tags, rs) {
+      process::exit(1);
+    }
+  } else if args.get(1).is_some_and(|a| a == "publish") {
+    let Some(name) = args.get(2) else {
+      eprintln!("Usage: publish <draft>");
+      process::exit(1);
+    };
+    if !website::publish(name) {
+      process::exit(1);
+    }
+  } else if args.iter().any(|a| a == "check") {
+    if !website::check() {
+      process::exit(1);
+    }
   } else {
     website::build();
   }
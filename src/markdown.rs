@@ -1,35 +1,216 @@
 use std::fs;
 use std::fs::File;
 use std::io::Write;
+use std::path::Path;
+use std::time::SystemTime;
 
-use crate::{ post::{Metadata, PathConfig}, path, io, rs2md };
+use crate::{ config::SiteConfig, post::{Metadata, PathConfig}, path, io, rs2md };
+use crate::callout;
+use crate::diagram;
+use crate::figure;
+use crate::math;
+use crate::task;
+use crate::wiki;
 
-// artifacts/*.md -> public/posts/*.html
-pub fn to_html_posts(posts: &Vec<Metadata>, paths: &PathConfig) {
+// artifacts/*.md -> public/posts/ at each post's permalink
+pub fn to_html_posts(
+  posts: &Vec<Metadata>,
+  paths: &PathConfig,
+  site: &SiteConfig,
+) {
   for p in posts {
     let md_file = path::markdown(&paths.artifacts, &p.name);
-    let html_file = path::html(&paths.public_posts, &p.name);
+    let html_file = p.output(paths);
+    fs::create_dir_all(html_file.parent().unwrap()).unwrap();
 
-    to_html_page(&md_file, &html_file);
+    to_html_page(&md_file, html_file.to_str().unwrap(), posts, paths, site);
   }
 }
 
-pub fn to_html_pages(pages: Vec<&str>, paths: &PathConfig) {
+pub fn to_html_pages(
+  pages: Vec<&str>,
+  posts: &[Metadata],
+  paths: &PathConfig,
+  site: &SiteConfig,
+) {
   for p in pages {
     to_html_page(
       paths.pages.join(format!("{p}.md")).to_str().unwrap(),
       paths.public.join(format!("{p}.html")).to_str().unwrap(),
+      posts,
+      paths,
+      site,
     );
   }
 }
 
-pub fn to_html_page(input: &str, output: &str) {
-  if path::modified(input) > path::modified(output) {
+pub fn to_html_page(
+  input: &str,
+  output: &str,
+  posts: &[Metadata],
+  paths: &PathConfig,
+  site: &SiteConfig,
+) {
+  let built = path::modified(output);
+  let contents = fs::read_to_string(input).unwrap();
+  if path::modified(input) > built || links_changed(&contents, posts, paths, built) {
     println!("  Converting {input}");
-    run_mmd(input, output);
+    // artifacts/diagrams/<hash>.svg
+    let contents = diagram::from_markdown(&contents, &paths.artifacts.join("diagrams"));
+
+    // Written next to the input so relative transclusions still resolve
+    let dir = Path::new(input).parent().unwrap();
+    let mut source =
+      tempfile::Builder::new().suffix(".md").tempfile_in(dir).unwrap();
+    source.write_all(preprocess(&contents, posts, site).as_bytes()).unwrap();
+
+    run_mmd(source.path().to_str().unwrap(), output);
   }
 }
 
+// [[wiki links]] take their text and URL from the post they link to, so a
+// page is out of date once a post it links to changes after it was built.
+// Links to unpublished posts may go live any day so they're always rebuilt.
+pub fn links_changed(contents: &str, posts: &[Metadata], paths: &PathConfig, built: SystemTime) -> bool {
+  wiki::linked(posts, contents).iter().any(|p| {
+    !p.visibility().is_public() || path::modified(&path::markdown(&paths.artifacts, &p.name)) > built
+  })
+}
+
+// Markdown extensions applied before handing over to MultiMarkdown. posts
+// are the targets of [[wiki links]]. Callouts go first as they may hold code
+// blocks. TeX math becomes MathML. {progress} counts the task list items of
+// the whole document.
+pub fn preprocess(contents: &str, posts: &[Metadata], site: &SiteConfig) -> String {
+  let contents = callout::from_markdown(contents);
+  let progress = task::progress(&contents);
+  transform_prose(&contents, |prose| {
+    let prose = task::from_markdown(prose, progress);
+    let prose = wiki::from_markdown(&prose, posts);
+    let prose = math::from_markdown(&prose);
+    figure::from_markdown(&prose, site.link_figures)
+  })
+}
+
+// Applies a transform to the prose of a document, leaving the metadata header
+// and fenced code blocks untouched
+fn transform_prose(contents: &str, transform: impl Fn(&str) -> String) -> String {
+  let header = metadata_len(contents);
+  let mut output = contents[..header].to_string();
+  let mut prose = String::new();
+
+  for (kind, line) in lines(&contents[header..]) {
+    if kind == Line::Prose {
+      prose.push_str(line);
+    } else {
+      output.push_str(&transform(&prose));
+      prose.clear();
+      output.push_str(line);
+    }
+  }
+
+  output.push_str(&transform(&prose));
+  output
+}
+
+// What a line of Markdown is to the preprocessors, which leave code alone
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Line {
+  Prose,
+  // A fence's opening and closing markers and the code between them
+  Open,
+  Code,
+  Close,
+}
+
+// Each line of a document, newline included, and what it is. Fences open with
+// three or more backticks or tildes at any indent and close with at least as
+// many of the same character. An unclosed fence runs to the end.
+pub fn lines(markdown: &str) -> Vec<(Line, &str)> {
+  let mut found = vec![];
+  let mut fence: Option<(char, usize)> = None;
+
+  for line in markdown.split_inclusive('\n') {
+    let trimmed = line.trim();
+    let kind =
+      if let Some((marker, len)) = fence {
+        let closes = trimmed.len() >= len && trimmed.chars().all(|c| c == marker);
+        if closes { fence = None; Line::Close } else { Line::Code }
+      } else {
+        let marker = trimmed.chars().next().filter(|c| *c == '`' || *c == '~');
+        let len = marker.map_or(0, |m| trimmed.chars().take_while(|c| *c == m).count());
+        match marker {
+          Some(marker) if len >= 3 => {
+            fence = Some((marker, len));
+            Line::Open
+          },
+          _ => Line::Prose,
+        }
+      };
+    found.push((kind, line));
+  }
+
+  found
+}
+
+// Length of the metadata header, which runs until the first blank line, or 0
+// when there isn't one
+pub fn metadata_len(markdown: &str) -> usize {
+  if !markdown.lines().next().is_some_and(is_metadata) { return 0; }
+
+  let mut len = 0;
+  for line in markdown.split_inclusive('\n') {
+    len += line.len();
+    if line.trim().is_empty() { break; }
+  }
+  len
+}
+
+// Applies transform to the text of a line between `inline code` spans
+pub fn outside_inline_code(line: &str, mut transform: impl FnMut(&str) -> String) -> String {
+  line
+    .split('`')
+    .enumerate()
+    .map(|(i, part)| if i % 2 == 0 { transform(part) } else { part.to_string() })
+    .collect::<Vec<String>>()
+    .join("`")
+}
+
+// Blanks out fenced and inline code, and the metadata header when asked,
+// keeping newlines and byte offsets so matches in what's left can be located
+// in the original
+pub fn mask(markdown: &str, metadata: bool) -> String {
+  let blank = |s: &str| -> String {
+    s.chars().map(|c| if c == '\n' { "\n".to_string() } else { " ".repeat(c.len_utf8()) }).collect()
+  };
+  let header = if metadata { metadata_len(markdown) } else { 0 };
+  let mut masked = blank(&markdown[..header]);
+
+  for (kind, line) in lines(&markdown[header..]) {
+    if kind == Line::Prose {
+      let parts: Vec<String> =
+        line
+        .split('`')
+        .enumerate()
+        .map(|(i, part)| if i % 2 == 0 { part.to_string() } else { blank(part) })
+        .collect();
+      // The backticks go too
+      masked.push_str(&parts.join(" "));
+    } else {
+      masked.push_str(&blank(line));
+    }
+  }
+
+  masked
+}
+
+pub fn is_metadata(line: &str) -> bool {
+  line.split_once(": ").is_some_and(|(key, _)| {
+    !key.is_empty()
+      && key.chars().all(|c| c.is_alphanumeric() || " _-".contains(c))
+  })
+}
+
 pub fn from_rs_or_md_to_md(config: &PathConfig) {
   let paths = io::paths_in_dir(&config.posts, &["md", "rs"]);
   for p in paths {
@@ -0,0 +1,375 @@
+use std::fmt::Write;
+use std::fs;
+use std::ops::Range;
+use std::path::PathBuf;
+
+use crate::check::Finding;
+use crate::html;
+use crate::markdown;
+
+// Commands standing for a single identifier
+const IDENTIFIERS: [(&str, &str); 40] = [
+  ("alpha", "α"), ("beta", "β"), ("gamma", "γ"), ("delta", "δ"),
+  ("epsilon", "ϵ"), ("varepsilon", "ε"), ("zeta", "ζ"), ("eta", "η"),
+  ("theta", "θ"), ("vartheta", "ϑ"), ("iota", "ι"), ("kappa", "κ"),
+  ("lambda", "λ"), ("mu", "μ"), ("nu", "ν"), ("xi", "ξ"), ("pi", "π"),
+  ("rho", "ρ"), ("sigma", "σ"), ("tau", "τ"), ("upsilon", "υ"), ("phi", "ϕ"),
+  ("varphi", "φ"), ("chi", "χ"), ("psi", "ψ"), ("omega", "ω"),
+  ("Gamma", "Γ"), ("Delta", "Δ"), ("Theta", "Θ"), ("Lambda", "Λ"),
+  ("Xi", "Ξ"), ("Pi", "Π"), ("Sigma", "Σ"), ("Phi", "Φ"), ("Psi", "Ψ"),
+  ("Omega", "Ω"), ("infty", "∞"), ("partial", "∂"), ("nabla", "∇"),
+  ("hbar", "ℏ"),
+];
+
+// Commands standing for an operator, relation or other symbol
+const OPERATORS: [(&str, &str); 41] = [
+  ("times", "×"), ("cdot", "⋅"), ("pm", "±"), ("mp", "∓"), ("div", "÷"),
+  ("le", "≤"), ("leq", "≤"), ("ge", "≥"), ("geq", "≥"), ("ne", "≠"),
+  ("neq", "≠"), ("approx", "≈"), ("equiv", "≡"), ("sim", "∼"),
+  ("propto", "∝"), ("to", "→"), ("rightarrow", "→"), ("leftarrow", "←"),
+  ("Rightarrow", "⇒"), ("Leftrightarrow", "⇔"), ("iff", "⇔"), ("in", "∈"),
+  ("notin", "∉"), ("subset", "⊂"), ("cup", "∪"), ("cap", "∩"),
+  ("forall", "∀"), ("exists", "∃"), ("sum", "∑"), ("prod", "∏"),
+  ("int", "∫"), ("oint", "∮"), ("ldots", "…"), ("cdots", "⋯"),
+  ("circ", "∘"), ("langle", "⟨"), ("rangle", "⟩"), ("lfloor", "⌊"),
+  ("rfloor", "⌋"), ("lceil", "⌈"), ("rceil", "⌉"),
+];
+
+// Function names set upright
+const FUNCTIONS: [&str; 19] = [
+  "sin", "cos", "tan", "sec", "csc", "cot", "arcsin", "arccos", "arctan",
+  "sinh", "cosh", "tanh", "log", "ln", "exp", "lim", "min", "max", "det",
+];
+
+// Accents placed over their argument
+const ACCENTS: [(&str, &str); 7] = [
+  ("vec", "→"), ("hat", "^"), ("bar", "¯"), ("overline", "‾"), ("dot", "˙"),
+  ("ddot", "¨"), ("tilde", "~"),
+];
+
+// Spacing commands and their widths
+const SPACES: [(&str, &str); 7] = [
+  (",", "0.1667em"), (":", "0.2222em"), (">", "0.2222em"), (";", "0.2778em"),
+  (" ", "0.25em"), ("quad", "1em"), ("qquad", "2em"),
+];
+
+// Converts $inline$ and $$display$$ TeX in prose to MathML. Math that doesn't
+// parse is left as it is for errors to report.
+pub fn from_markdown(prose: &str) -> String {
+  let mut output = prose.to_string();
+  for (range, display) in spans(&markdown::mask(prose, false)).into_iter().rev() {
+    let delimiter = if display { 2 } else { 1 };
+    let tex = &prose[range.start + delimiter..range.end - delimiter];
+    if let Ok(mathml) = to_mathml(tex, display) {
+      output.replace_range(range, &mathml);
+    }
+  }
+  output
+}
+
+// Math in the Markdown files that doesn't parse, located by line
+pub fn errors(files: &[PathBuf]) -> Vec<Finding> {
+  let mut findings = vec![];
+
+  for file in files {
+    let Ok(contents) = fs::read_to_string(file) else { continue };
+    for (range, display) in spans(&markdown::mask(&contents, true)) {
+      let delimiter = if display { 2 } else { 1 };
+      let tex = &contents[range.start + delimiter..range.end - delimiter];
+      if let Err(message) = to_mathml(tex, display) {
+        findings.push(Finding {
+          file: file.to_str().unwrap().to_string(),
+          line: html::line_at(&contents, range.start),
+          message: format!("math: {message}"),
+        });
+      }
+    }
+  }
+
+  findings
+}
+
+// A <math> element for TeX, as a block for display math. The source is kept
+// as an annotation.
+pub fn to_mathml(tex: &str, display: bool) -> Result<String, String> {
+  let mut parser = Parser { chars: tex.chars().collect(), pos: 0 };
+  let row = parser.row(None)?;
+  let display = if display { " display=\"block\"" } else { "" };
+  Ok(format!(
+    "<math{display}><semantics><mrow>{}</mrow><annotation encoding=\"application/x-tex\">{}</annotation></semantics></math>",
+    row.concat(),
+    text(tex.trim()),
+  ))
+}
+
+// Byte ranges of math in text, including delimiters, and whether each is
+// display math. Inline math has no space inside its $s and isn't followed by a
+// digit so prices like $5 and $10 are left alone.
+fn spans(text: &str) -> Vec<(Range<usize>, bool)> {
+  let bytes = text.as_bytes();
+  let mut found = vec![];
+  let mut i = 0;
+
+  while i < bytes.len() {
+    match bytes[i] {
+      b'\\' => i += 2,
+      b'$' if bytes.get(i + 1) == Some(&b'$') => {
+        match text[i + 2..].find("$$") {
+          Some(end) => {
+            found.push((i..i + 2 + end + 2, true));
+            i += end + 4;
+          },
+          None => i += 2,
+        }
+      },
+      b'$' if bytes.get(i + 1).is_some_and(|b| !b.is_ascii_whitespace()) => {
+        let line_end = text[i..].find('\n').map_or(text.len(), |n| i + n);
+        let close = (i + 2..line_end).find(|&j| {
+          bytes[j] == b'$'
+            && bytes[j - 1] != b'\\'
+            && !bytes[j - 1].is_ascii_whitespace()
+            && !bytes.get(j + 1).is_some_and(u8::is_ascii_digit)
+        });
+        match close {
+          Some(j) => {
+            found.push((i..j + 1, false));
+            i = j + 1;
+          },
+          None => i += 1,
+        }
+      },
+      _ => i += 1,
+    }
+  }
+
+  found
+}
+
+// Text content with everything but letters, digits and spaces as character
+// references so MultiMarkdown leaves it alone
+fn text(s: &str) -> String {
+  s.chars().fold(String::new(), |mut text, c| {
+    if c.is_alphanumeric() || c == ' ' {
+      text.push(c);
+    } else {
+      write!(text, "&#{};", u32::from(c)).unwrap();
+    }
+    text
+  })
+}
+
+fn element(name: &str, content: &str) -> String {
+  format!("<{name}>{}</{name}>", text(content))
+}
+
+struct Parser {
+  chars: Vec<char>,
+  pos: usize,
+}
+
+impl Parser {
+  fn peek(&self) -> Option<char> {
+    self.chars.get(self.pos).copied()
+  }
+
+  fn skip_whitespace(&mut self) {
+    while self.peek().is_some_and(char::is_whitespace) {
+      self.pos += 1;
+    }
+  }
+
+  // Terms with their scripts until the closing character or the end
+  fn row(&mut self, until: Option<char>) -> Result<Vec<String>, String> {
+    let mut terms = vec![];
+    loop {
+      self.skip_whitespace();
+      match self.peek() {
+        None if until.is_some() => {
+          return Err(format!("missing {}", until.unwrap()));
+        },
+        None => return Ok(terms),
+        Some(c) if Some(c) == until => {
+          self.pos += 1;
+          return Ok(terms);
+        },
+        Some('}') => return Err("unexpected }".to_string()),
+        _ => {
+          let base = self.atom()?;
+          terms.push(self.scripts(base)?);
+        },
+      }
+    }
+  }
+
+  // Wraps base in any following ^ and _ scripts
+  fn scripts(&mut self, base: String) -> Result<String, String> {
+    let (mut sub, mut sup) = (None, None);
+    loop {
+      self.skip_whitespace();
+      match self.peek() {
+        Some('_') if sub.is_none() => {
+          self.pos += 1;
+          sub = Some(self.argument("_")?);
+        },
+        Some('^') if sup.is_none() => {
+          self.pos += 1;
+          sup = Some(self.argument("^")?);
+        },
+        Some('\'') => {
+          self.pos += 1;
+          let prime = element("mo", "′");
+          sup = Some(sup.map_or(prime.clone(), |s| format!("<mrow>{s}{prime}</mrow>")));
+        },
+        Some('_' | '^') => return Err("double script".to_string()),
+        _ => break,
+      }
+    }
+
+    Ok(match (sub, sup) {
+      (None, None) => base,
+      (Some(sub), None) => format!("<msub>{base}{sub}</msub>"),
+      (None, Some(sup)) => format!("<msup>{base}{sup}</msup>"),
+      (Some(sub), Some(sup)) => format!("<msubsup>{base}{sub}{sup}</msubsup>"),
+    })
+  }
+
+  // A {group} or a single atom, as a command's argument
+  fn argument(&mut self, of: &str) -> Result<String, String> {
+    self.skip_whitespace();
+    match self.peek() {
+      None | Some('}') => Err(format!("missing argument for {of}")),
+      Some('{') => {
+        self.pos += 1;
+        Ok(format!("<mrow>{}</mrow>", self.row(Some('}'))?.concat()))
+      },
+      _ => self.atom(),
+    }
+  }
+
+  // The raw text of a {group}
+  fn raw_argument(&mut self, of: &str) -> Result<String, String> {
+    self.skip_whitespace();
+    if self.peek() != Some('{') {
+      return Err(format!("missing {{ after {of}"));
+    }
+    let start = self.pos + 1;
+    let mut depth = 0;
+    while let Some(c) = self.peek() {
+      self.pos += 1;
+      match c {
+        '{' => depth += 1,
+        '}' if depth == 1 => return Ok(self.chars[start..self.pos - 1].iter().collect()),
+        '}' => depth -= 1,
+        _ => (),
+      }
+    }
+    Err(format!("missing }} after {of}"))
+  }
+
+  fn atom(&mut self) -> Result<String, String> {
+    let c = self.peek().ok_or("unexpected end")?;
+    self.pos += 1;
+    match c {
+      '{' => Ok(format!("<mrow>{}</mrow>", self.row(Some('}'))?.concat())),
+      '\\' => self.command(),
+      '0'..='9' | '.' => {
+        let start = self.pos - 1;
+        while self.peek().is_some_and(|c| c.is_ascii_digit() || c == '.') {
+          self.pos += 1;
+        }
+        Ok(element("mn", &self.chars[start..self.pos].iter().collect::<String>()))
+      },
+      c if c.is_alphabetic() => Ok(element("mi", &c.to_string())),
+      '-' => Ok(element("mo", "−")),
+      '*' => Ok(element("mo", "∗")),
+      '+' | '=' | '<' | '>' | '(' | ')' | '[' | ']' | '|' | ',' | ';' | ':' | '!' | '/' => {
+        Ok(element("mo", &c.to_string()))
+      },
+      '^' | '_' => Err(format!("{c} without a base")),
+      c => Err(format!("unexpected {c}")),
+    }
+  }
+
+  fn command(&mut self) -> Result<String, String> {
+    let start = self.pos;
+    while self.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+      self.pos += 1;
+    }
+    if self.pos == start && self.peek().is_some() {
+      self.pos += 1;
+    }
+    let name: String = self.chars[start..self.pos].iter().collect();
+    let lookup = |table: &[(&str, &'static str)]| {
+      table.iter().find(|(n, _)| *n == name).map(|(_, v)| *v)
+    };
+
+    if let Some(symbol) = lookup(&IDENTIFIERS) {
+      return Ok(element("mi", symbol));
+    }
+    if let Some(symbol) = lookup(&OPERATORS) {
+      return Ok(element("mo", symbol));
+    }
+    if let Some(width) = lookup(&SPACES) {
+      return Ok(format!("<mspace width=\"{width}\"/>"));
+    }
+    if let Some(accent) = lookup(&ACCENTS) {
+      let base = self.argument(&format!("\\{name}"))?;
+      return Ok(format!("<mover accent=\"true\">{base}{}</mover>", element("mo", accent)));
+    }
+    if FUNCTIONS.contains(&name.as_str()) {
+      return Ok(element("mi", &name));
+    }
+
+    match name.as_str() {
+      "frac" | "dfrac" | "tfrac" => {
+        let numerator = self.argument("\\frac")?;
+        let denominator = self.argument("\\frac")?;
+        Ok(format!("<mfrac>{numerator}{denominator}</mfrac>"))
+      },
+      "sqrt" => {
+        self.skip_whitespace();
+        if self.peek() == Some('[') {
+          self.pos += 1;
+          let index = self.row(Some(']'))?.concat();
+          let base = self.argument("\\sqrt")?;
+          Ok(format!("<mroot>{base}<mrow>{index}</mrow></mroot>"))
+        } else {
+          Ok(format!("<msqrt>{}</msqrt>", self.argument("\\sqrt")?))
+        }
+      },
+      "text" | "mathrm" => Ok(element("mtext", &self.raw_argument(&format!("\\{name}"))?)),
+      "operatorname" => Ok(element("mi", &self.raw_argument("\\operatorname")?)),
+      "mathbf" | "boldsymbol" => {
+        let letters = self.raw_argument(&format!("\\{name}"))?;
+        Ok(format!("<mi mathvariant=\"bold\">{}</mi>", text(&letters)))
+      },
+      "left" | "right" => {
+        self.skip_whitespace();
+        let delimiter =
+          match self.peek() {
+            Some('\\') => {
+              self.pos += 1;
+              let symbol = self.command()?;
+              // \left\{ and friends come back as elements already
+              return Ok(symbol.replacen("<mo>", "<mo fence=\"true\" stretchy=\"true\">", 1));
+            },
+            Some('.') => String::new(),
+            Some(c) => c.to_string(),
+            None => return Err(format!("missing delimiter after \\{name}")),
+          };
+        self.pos += 1;
+        if delimiter.is_empty() {
+          Ok(String::new())
+        } else {
+          Ok(format!("<mo fence=\"true\" stretchy=\"true\">{}</mo>", text(&delimiter)))
+        }
+      },
+      "{" | "}" | "|" => Ok(element("mo", if name == "|" { "‖" } else { &name })),
+      "%" | "#" | "$" | "&" | "_" => Ok(element("mi", &name)),
+      // Negative space isn't possible in MathML so is dropped
+      "!" => Ok(String::new()),
+      "" => Err("\\ at end".to_string()),
+      _ => Err(format!("unknown command \\{name}")),
+    }
+  }
+}
@@ -0,0 +1,220 @@
+use std::borrow::Cow;
+use std::fs;
+use std::path::Path;
+
+use crate::io;
+
+// Whitespace next to these tags never renders so it can be dropped entirely
+const BLOCK_TAGS: [&str; 45] = [
+  "!doctype", "html", "head", "body", "title", "meta", "link", "script",
+  "style", "base", "noscript", "template", "nav", "header", "footer", "main",
+  "article", "section", "aside", "div", "p", "ul", "ol", "li", "dl", "dt",
+  "dd", "h1", "h2", "h3", "h4", "h5", "h6", "figure", "figcaption",
+  "blockquote", "pre", "hr", "br", "table", "thead", "tbody", "tr", "td", "th",
+];
+
+// Elements whose content is copied as is
+const RAW_TAGS: [&str; 4] = ["pre", "textarea", "script", "style"];
+
+enum Token<'a> {
+  Tag(&'a str, String),
+  Text(&'a str),
+  Raw(Cow<'a, str>),
+}
+
+// Minifies every HTML and CSS file below root in place, printing the size of
+// each before and after. Returns the total sizes.
+pub fn run(root: &Path) -> (usize, usize) {
+  let mut total = (0, 0);
+
+  for file in io::files_in_tree(root) {
+    let ext = file.extension().unwrap_or_default().to_str().unwrap();
+    let minified = match ext {
+      "html" => html(&fs::read_to_string(&file).unwrap()),
+      "css" => css(&fs::read_to_string(&file).unwrap()),
+      _ => continue,
+    };
+
+    let before = fs::metadata(&file).unwrap().len().try_into().unwrap();
+    let after = minified.len();
+    fs::write(&file, minified).unwrap();
+    println!(
+      "  {} {}",
+      file.strip_prefix(root).unwrap().to_str().unwrap(),
+      report(before, after),
+    );
+    total = (total.0 + before, total.1 + after);
+  }
+
+  println!("  Total {}", report(total.0, total.1));
+  total
+}
+
+// Size change as a percentage, which is a growth for already minimal files
+fn report(before: usize, after: usize) -> String {
+  let change =
+    if after > before {
+      format!("+{}%", (after - before) * 100 / before.max(1))
+    } else {
+      format!("-{}%", (before - after) * 100 / before.max(1))
+    };
+  format!("{before} -> {after} bytes ({change})")
+}
+
+// Removes comments and collapses whitespace. <pre>, <textarea> and <script>
+// are left untouched, <style> gets the CSS treatment.
+pub fn html(html: &str) -> String {
+  let tokens = tokenize(html);
+  let mut output = String::new();
+
+  for (i, token) in tokens.iter().enumerate() {
+    match token {
+      Token::Tag(tag, _) => output.push_str(tag),
+      Token::Raw(raw) => output.push_str(raw),
+      Token::Text(text) => {
+        let mut text = collapse(text);
+        if i == 0 || is_block(tokens.get(i - 1)) {
+          text = text.trim_start().to_string();
+        }
+        if is_block(tokens.get(i + 1)) || i + 1 == tokens.len() {
+          text = text.trim_end().to_string();
+        }
+        output.push_str(&text);
+      },
+    }
+  }
+
+  output
+}
+
+fn tokenize(html: &str) -> Vec<Token<'_>> {
+  let mut tokens = vec![];
+  let mut rest = html;
+
+  while !rest.is_empty() {
+    if rest.starts_with("<!--") {
+      let end = rest.find("-->").map_or(rest.len(), |i| i + 3);
+      // Keep conditional comments, drop the rest
+      if rest.starts_with("<!--[if") {
+        tokens.push(Token::Raw(Cow::Borrowed(&rest[..end])));
+      }
+      rest = &rest[end..];
+    } else if rest.starts_with('<') {
+      let end = tag_end(rest);
+      let tag = &rest[..end];
+      let name = tag_name(tag);
+      rest = &rest[end..];
+      tokens.push(Token::Tag(tag, name.clone()));
+
+      let opening = !tag.starts_with("</") && !tag.ends_with("/>");
+      if opening && RAW_TAGS.contains(&name.as_str()) {
+        let close = format!("</{name}");
+        let end = rest.to_ascii_lowercase().find(&close).unwrap_or(rest.len());
+        let content = &rest[..end];
+        tokens.push(Token::Raw(
+          if name == "style" { Cow::Owned(css(content)) } else { Cow::Borrowed(content) }
+        ));
+        rest = &rest[end..];
+      }
+    } else {
+      let end = rest.find('<').unwrap_or(rest.len());
+      tokens.push(Token::Text(&rest[..end]));
+      rest = &rest[end..];
+    }
+  }
+
+  tokens
+}
+
+// Index just past the closing > of the tag at the start, skipping quoted values
+fn tag_end(html: &str) -> usize {
+  let mut quote = None;
+  for (i, c) in html.char_indices() {
+    match (quote, c) {
+      (None, '"' | '\'') => quote = Some(c),
+      (Some(q), c) if q == c => quote = None,
+      (None, '>') => return i + 1,
+      _ => (),
+    }
+  }
+  html.len()
+}
+
+fn tag_name(tag: &str) -> String {
+  tag
+    .trim_start_matches(['<', '/'])
+    .chars()
+    .take_while(|c| c.is_ascii_alphanumeric() || *c == '!' || *c == '-')
+    .collect::<String>()
+    .to_ascii_lowercase()
+}
+
+fn is_block(token: Option<&Token>) -> bool {
+  matches!(token, Some(Token::Tag(_, name)) if BLOCK_TAGS.contains(&name.as_str()))
+}
+
+fn collapse(text: &str) -> String {
+  let mut collapsed = String::new();
+  let mut space = false;
+  for c in text.chars() {
+    if c.is_whitespace() {
+      space = true;
+    } else {
+      if space { collapsed.push(' '); }
+      space = false;
+      collapsed.push(c);
+    }
+  }
+  if space { collapsed.push(' '); }
+  collapsed
+}
+
+// Removes comments and any whitespace that doesn't separate selectors or
+// values. Strings are left as they are.
+pub fn css(css: &str) -> String {
+  let mut output = String::new();
+  let mut chars = css.chars().peekable();
+  let mut space = false;
+
+  while let Some(c) = chars.next() {
+    match c {
+      '/' if chars.peek() == Some(&'*') => {
+        chars.next();
+        let mut last = ' ';
+        for c in chars.by_ref() {
+          if last == '*' && c == '/' { break; }
+          last = c;
+        }
+        space = true;
+      },
+      '"' | '\'' => {
+        flush_space(&mut output, &mut space, c);
+        output.push(c);
+        for s in chars.by_ref() {
+          output.push(s);
+          if s == c { break; }
+        }
+      },
+      c if c.is_whitespace() => space = true,
+      c => {
+        flush_space(&mut output, &mut space, c);
+        if c == '}' && output.ends_with(';') {
+          output.pop();
+        }
+        output.push(c);
+      },
+    }
+  }
+
+  output
+}
+
+// Writes a pending space unless it sits next to punctuation that doesn't
+// need one
+fn flush_space(output: &mut String, space: &mut bool, next: char) {
+  let after = output.chars().last().is_none_or(|c| "{};:,>".contains(c));
+  if *space && !after && !"{};,>".contains(next) {
+    output.push(' ');
+  }
+  *space = false;
+}
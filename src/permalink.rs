@@ -0,0 +1,91 @@
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::post::{self, Metadata, PathConfig};
+use crate::string::escape_html;
+
+// The URL posts have always had, used when no pattern is configured
+pub const DEFAULT: &str = "/posts/:name.html";
+
+// Sets each post's URL from a pattern of :name (the filename stem), :slug,
+// :year, :month and :day. Patterns ending in / give directory URLs served
+// from index.html. Posts without a date keep the default URL.
+pub fn apply(posts: &mut [Metadata], pattern: &str) {
+  for p in posts {
+    p.permalink = expand(pattern, p);
+  }
+}
+
+pub fn expand(pattern: &str, p: &Metadata) -> String {
+  let pattern = if pattern.is_empty() { DEFAULT } else { pattern };
+  let dated = [":year", ":month", ":day"].iter().any(|t| pattern.contains(t));
+  if dated && !post::is_date(&p.created) {
+    return expand(DEFAULT, p);
+  }
+
+  let date = if post::is_date(&p.created) { p.created.as_str() } else { "" };
+  pattern
+    .replace(":name", &p.name)
+    .replace(":slug", &slug(p))
+    .replace(":year", date.get(0..4).unwrap_or_default())
+    .replace(":month", date.get(5..7).unwrap_or_default())
+    .replace(":day", date.get(8..10).unwrap_or_default())
+}
+
+// `slug:` metadata or the filename without its date
+pub fn slug(p: &Metadata) -> String {
+  if !p.slug.is_empty() { return p.slug.clone(); }
+
+  let dated = p.name.get(..10).is_some_and(post::is_date) && p.name.get(10..11) == Some("-");
+  if dated { p.name[11..].to_string() } else { p.name.clone() }
+}
+
+// The generated file for a URL. URLs under /posts/ live in public_posts.
+pub fn output(url: &str, paths: &PathConfig) -> PathBuf {
+  let path =
+    match url.strip_prefix("/posts/") {
+      Some(rest) => paths.public_posts.join(rest),
+      None => paths.public.join(url.trim_start_matches('/')),
+    };
+
+  if url.ends_with('/') { path.join("index.html") } else { path }
+}
+
+// Writes a meta refresh page at each post's `aliases:` and lists them in
+// _redirects for Cloudflare Pages to answer with a 301
+pub fn redirects(posts: &[Metadata], paths: &PathConfig) {
+  let mut rules = String::new();
+
+  for p in posts.iter().filter(|p| !p.visibility().is_draft()) {
+    let url = p.url();
+    for alias in p.aliases.split_whitespace().filter(|alias| *alias != url) {
+      let stub = output(alias, paths);
+      fs::create_dir_all(stub.parent().unwrap()).unwrap();
+      fs::write(&stub, stub_html(&url, &p.title)).unwrap();
+      writeln!(rules, "{alias} {url} 301").unwrap();
+    }
+  }
+
+  fs::write(paths.public.join("_redirects"), rules).unwrap();
+}
+
+fn stub_html(url: &str, title: &str) -> String {
+  let url = escape_html(url);
+  let title = escape_html(title);
+
+  format!(r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<meta name="robots" content="noindex">
+<link rel="canonical" href="{url}">
+<meta http-equiv="refresh" content="0; url={url}">
+</head>
+<body>
+<p>This post has moved to <a href="{url}">{title}</a>.</p>
+</body>
+</html>
+"#)
+}
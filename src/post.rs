@@ -4,10 +4,19 @@ use std::fs;
 use std::io::Write;
 use std::path::PathBuf;
 
+use crate::archive;
+use crate::config::SiteConfig;
+use crate::date;
 use crate::io;
-use crate::string::format_or_empty;
+use crate::permalink;
+use crate::path;
+use crate::related;
+use crate::seo;
+use crate::series;
+use crate::string::{format_or_empty, replace_section};
+use crate::wiki;
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct Metadata {
   pub name: String,
   pub title: String,
@@ -15,6 +24,87 @@ pub struct Metadata {
   pub updated: String,
   pub tags: String,
   pub intro: String,
+  // Kept out of sitemap.xml and disallowed in robots.txt
+  pub noindex: bool,
+  // Summary for search engines and link previews, defaults to the intro
+  pub description: String,
+  // Preview image, defaults to the first image in the post
+  pub image: String,
+  // Names of the posts to list as related instead of the computed ones
+  pub related: String,
+  // Name of the series the post belongs to and its place in it when not by
+  // date
+  pub series: String,
+  pub series_order: Option<usize>,
+  // 1 based position within the series, set by series::number
+  pub part: usize,
+  // Kept at the top of the home page
+  pub pinned: bool,
+  // Dates the post appears in and drops out of listings
+  pub publish: String,
+  pub expires: String,
+  // Replaces the filename without its date in permalinks
+  pub slug: String,
+  // Old URLs redirected to the post
+  pub aliases: String,
+  // Kind of page, e.g. devlog pages have their progress on the home page
+  pub category: String,
+  // URL from the permalink pattern, set by permalink::apply
+  pub permalink: String,
+}
+
+// Where a post shows up. Drafts and scheduled posts are only built locally,
+// expired ones are built but left out of listings.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Visibility {
+  Published,
+  Draft,
+  Scheduled,
+  Expired,
+}
+
+impl Visibility {
+  // Left out of production builds
+  pub fn is_draft(self) -> bool {
+    matches!(self, Visibility::Draft | Visibility::Scheduled)
+  }
+
+  // Listed on the home page, in the archive, sitemap, search and so on
+  pub fn is_public(self) -> bool {
+    self == Visibility::Published
+  }
+}
+
+impl Metadata {
+  pub fn url(&self) -> String {
+    if self.permalink.is_empty() {
+      format!("/posts/{}.html", self.name)
+    } else {
+      self.permalink.clone()
+    }
+  }
+
+  // Where the post's HTML is generated
+  pub fn output(&self, paths: &PathConfig) -> PathBuf {
+    permalink::output(&self.url(), paths)
+  }
+
+  pub fn visibility(&self) -> Visibility {
+    self.visibility_on(&date::today())
+  }
+
+  // A draft- filename wins, then publish: and expires: dates
+  pub fn visibility_on(&self, today: &str) -> Visibility {
+    if self.name.starts_with("draft-") {
+      Visibility::Draft
+    } else if is_date(&self.publish) && self.publish.as_str() > today {
+      Visibility::Scheduled
+    } else if is_date(&self.expires) && self.expires.as_str() <= today {
+      Visibility::Expired
+    } else {
+      Visibility::Published
+    }
+  }
 }
 
 pub struct PathConfig {
@@ -23,25 +113,48 @@ pub struct PathConfig {
   pub artifacts: PathBuf,
   pub public: PathBuf,
   pub public_posts: PathBuf,
+  pub dist: PathBuf,
 }
 
 // Builds the metadata struct for all posts
 pub fn build_all(paths: &PathConfig) -> Vec<Metadata> {
-  io::paths_in_dir(&paths.artifacts, &["md"])
+  let mut posts: Vec<Metadata> =
+    io::paths_in_dir(&paths.artifacts, &["md"])
     .iter()
     .map(build)
+    .collect();
+  series::number(&mut posts);
+  posts
+}
+
+// Builds the metadata struct for the named pages in pages/
+pub fn build_pages(pages: &[&str], paths: &PathConfig) -> Vec<Metadata> {
+  pages
+    .iter()
+    .map(|p| build(&paths.pages.join(format!("{p}.md"))))
     .collect()
 }
 
 // Reload the generated HTML posts and insert tags, created & updated dates
-// And insert highlightjs.html into <head>
-pub fn post_process(posts: &[Metadata], paths: &PathConfig) {
+// And insert highlightjs.html and the SEO metadata into <head>, a banner on
+// drafts and the series navigation, older/newer links and related posts at
+// the end of <body>
+pub fn post_process(posts: &[Metadata], paths: &PathConfig, site: &SiteConfig) {
   let highlightjs = io::load_template("highlightjs");
+  let generated_description_re =
+    Regex::new(r#"[ \t]*<meta name="description" content="[^"]*"/>\n?"#).unwrap();
+  let sources: Vec<String> =
+    posts
+    .iter()
+    .map(|p| fs::read_to_string(path::markdown(&paths.artifacts, &p.name)).unwrap_or_default())
+    .collect();
+  let related = related::find(posts, &sources, site.related_posts);
+  let backlinks = wiki::backlinks(posts, &sources);
 
-  for p in posts {
+  for ((p, related), backlinks) in posts.iter().zip(related).zip(backlinks) {
     let created = format_or_empty("Published: ", &p.created);
     let updated = format_or_empty("Updated: ", &p.updated);
-    let path = paths.public_posts.join(format!("{}.html", &p.name));
+    let path = p.output(paths);
     let original_html =
       fs::read_to_string(&path)
       .unwrap_or_else(|e| panic!("Could not open: {}.\n{}", path.to_str().unwrap(), e));
@@ -53,6 +166,19 @@ pub fn post_process(posts: &[Metadata], paths: &PathConfig) {
       .replace("{tags}", &p.tags)
       .replace("{created}", &created)
       .replace("{updated}", &updated);
+    // MultiMarkdown writes a description meta of its own from the metadata
+    let html = generated_description_re.replace(&html, "");
+    let html = replace_section(&html, "seo", &seo::post_head(p, site), "</head>");
+    let html = replace_section(&html, "status", &banner(p), "<article class=\"post\">");
+    // Series, older/newer links, related posts then backlinks at the end of
+    // <body>
+    let html = replace_section(&html, "backlinks", &wiki::render(&backlinks), "</body>");
+    let anchor = section_or_end(&html, &["backlinks"]);
+    let html = replace_section(&html, "related", &related::render(&related), &anchor);
+    let anchor = section_or_end(&html, &["related", "backlinks"]);
+    let html = replace_section(&html, "chronology", &archive::navigation(p, posts), &anchor);
+    let anchor = section_or_end(&html, &["chronology", "related", "backlinks"]);
+    let html = replace_section(&html, "series", &series::navigation(p, posts), &anchor);
 
     if html != original_html {
       let mut post = File::create(path).unwrap();
@@ -62,6 +188,30 @@ pub fn post_process(posts: &[Metadata], paths: &PathConfig) {
   }
 }
 
+// Tells local readers a post won't be deployed yet
+fn banner(p: &Metadata) -> String {
+  let message =
+    match p.visibility() {
+      Visibility::Draft => "Draft, left out of production builds".to_string(),
+      Visibility::Scheduled => {
+        format!("Scheduled for {}, left out of production builds until then", p.publish)
+      },
+      _ => return String::new(),
+    };
+
+  format!("<p class=\"banner\">{message}</p>\n")
+}
+
+// The first of the named sections present, so a section inserted before it
+// stays ahead of them, or the end of <body>
+fn section_or_end(html: &str, sections: &[&str]) -> String {
+  sections
+    .iter()
+    .map(|name| format!("<!-- {name} -->"))
+    .find(|marker| html.contains(marker.as_str()))
+    .unwrap_or_else(|| "</body>".to_string())
+}
+
 // Populates the metadata struct which allows the Home page to be generated
 pub fn build(path: &PathBuf) -> Metadata {
   let contents = fs::read_to_string(path).unwrap();
@@ -70,6 +220,18 @@ pub fn build(path: &PathBuf) -> Metadata {
   let mut created =  String::new();
   let mut updated = String::new();
   let mut tags = String::new();
+  let mut noindex = false;
+  let mut description = String::new();
+  let mut image = String::new();
+  let mut related = String::new();
+  let mut series = String::new();
+  let mut series_order = None;
+  let mut pinned = false;
+  let mut publish = String::new();
+  let mut expires = String::new();
+  let mut slug = String::new();
+  let mut aliases = String::new();
+  let mut category = String::new();
   let mut is_metadata = true;
   let mut intro = String::new();
   let heading_re = Regex::new(r"^# ").unwrap();
@@ -92,6 +254,18 @@ pub fn build(path: &PathBuf) -> Metadata {
         "created" => created = String::from(value),
         "updated" => updated = String::from(value),
         "tags" => tags = String::from(value),
+        "noindex" => noindex = value == "true",
+        "description" => description = String::from(value),
+        "image" => image = String::from(value),
+        "related" => related = String::from(value),
+        "series" => series = unescape(value),
+        "series_order" => series_order = value.parse().ok(),
+        "pinned" => pinned = value == "true",
+        "publish" => publish = String::from(value),
+        "expires" => expires = String::from(value),
+        "slug" => slug = String::from(value),
+        "aliases" => aliases = String::from(value),
+        "category" => category = String::from(value),
         _ => (), // Ignore unrecognised metadata
       }
 
@@ -108,23 +282,52 @@ pub fn build(path: &PathBuf) -> Metadata {
     }
   }
 
+  if image.is_empty() {
+    let image_re = Regex::new(r"!\[[^\]]*\]\(\s*(\S+?)[\s)]").unwrap();
+    if let Some(caps) = image_re.captures(&contents) {
+      image = caps[1].to_string();
+    }
+  }
+
   let filename = path.file_name().unwrap().to_str().unwrap();
-  Metadata {
+  let mut post = Metadata {
     name: String::from(&filename[0..filename.len() - 3]),
     title,
-    created: if created.is_empty() { get_created(filename) } else { created },
+    created,
     updated,
     tags,
     intro,
+    noindex,
+    description,
+    image,
+    related,
+    series,
+    series_order,
+    pinned,
+    publish,
+    expires,
+    slug,
+    aliases,
+    category,
+    ..Default::default()
+  };
+  if post.created.is_empty() {
+    post.created = get_created(&post, filename);
   }
+  post
+}
+
+// Whether the value is a YYYY-MM-DD date rather than a placeholder like draft
+pub fn is_date(value: &str) -> bool {
+  Regex::new(r"^\d{4}-\d{2}-\d{2}$").unwrap().is_match(value)
 }
 
 fn unescape(s: &str) -> String {
   s.replace("\\#", "#")
 }
 
-fn get_created(filename: &str) -> String {
-  if filename.starts_with("draft-") { return "draft".to_string() }
+fn get_created(p: &Metadata, filename: &str) -> String {
+  if p.visibility() == Visibility::Draft { return "draft".to_string() }
   if !filename.starts_with("20") { return filename.to_string() }
 
   filename[0..10].to_string()
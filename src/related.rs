@@ -0,0 +1,70 @@
+use std::collections::HashSet;
+
+use crate::index;
+use crate::post::Metadata;
+use crate::search;
+
+// Points for each shared tag. Term overlap scores up to 100 points for posts
+// using exactly the same terms.
+const TAG_POINTS: usize = 20;
+
+// For each post, the `count` posts most related to it by shared tags and the
+// overlap of the terms in their text. Posts with `related:` metadata get the
+// posts it names instead. Only published posts are suggested.
+pub fn find<'a>(posts: &'a [Metadata], sources: &[String], count: usize) -> Vec<Vec<&'a Metadata>> {
+  let terms: Vec<HashSet<String>> =
+    sources
+    .iter()
+    .map(|source| {
+      let (headings, body) = search::text(source);
+      search::terms_in(&format!("{headings}\n{body}")).into_iter().collect()
+    })
+    .collect();
+
+  posts.iter().enumerate().map(|(i, p)| {
+    if !p.related.is_empty() {
+      // Drafts, scheduled and expired posts aren't there to link to
+      return p.related.split_whitespace().filter_map(|name| {
+        let found = posts.iter().find(|other| other.name == name);
+        match found {
+          None => println!("  {}: related post {name} not found", p.name),
+          Some(other) if !other.visibility().is_public() => {
+            println!("  {}: related post {name} isn't published", p.name);
+          },
+          Some(_) => (),
+        }
+        found.filter(|other| other.visibility().is_public())
+      }).collect();
+    }
+
+    let tags: HashSet<&str> = p.tags.split_whitespace().collect();
+    let mut scored: Vec<(usize, &Metadata)> =
+      posts
+      .iter()
+      .enumerate()
+      .filter(|(j, other)| *j != i && other.visibility().is_public())
+      .map(|(j, other)| {
+        let shared_tags = other.tags.split_whitespace().filter(|t| tags.contains(t)).count();
+        let union = terms[i].union(&terms[j]).count().max(1);
+        let overlap = terms[i].intersection(&terms[j]).count();
+        (shared_tags * TAG_POINTS + overlap * 100 / union, other)
+      })
+      .filter(|(score, _)| *score > 0)
+      .collect();
+    // Highest score first, newest first when tied
+    scored.sort_by(|(a, p), (b, q)| b.cmp(a).then_with(|| q.name.cmp(&p.name)));
+
+    scored.into_iter().take(count).map(|(_, other)| other).collect()
+  }).collect()
+}
+
+// A "Related posts" section of cards, empty when there are none
+pub fn render(related: &[&Metadata]) -> String {
+  if related.is_empty() { return String::new(); }
+
+  let cards: Vec<String> = related.iter().map(|p| index::card(p)).collect();
+  format!(
+    "<section class=\"related\">\n<h2>Related posts</h2>\n<div class=\"cards\">\n{}\n</div>\n</section>\n",
+    cards.join("\n"),
+  )
+}
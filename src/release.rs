@@ -0,0 +1,146 @@
+use regex::Regex;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt::{self, Write};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::check::{self, Finding, Sources};
+use crate::html;
+use crate::io;
+use crate::post::Metadata;
+use crate::string::hex;
+
+// Content hash and size of every deployed file by its path relative to the
+// deployed directory
+pub type Manifest = BTreeMap<String, (String, u64)>;
+
+#[derive(Debug, PartialEq)]
+pub enum Change {
+  Added(String, u64),
+  // Size before and after
+  Changed(String, u64, u64),
+  Removed(String, u64),
+}
+
+impl fmt::Display for Change {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Change::Added(path, size) => write!(f, "+ {path} ({size} bytes)"),
+      Change::Changed(path, before, after) => write!(f, "~ {path} ({before} -> {after} bytes)"),
+      Change::Removed(path, size) => write!(f, "- {path} ({size} bytes)"),
+    }
+  }
+}
+
+pub fn manifest(root: &Path) -> Manifest {
+  io::files_in_tree(root)
+    .into_iter()
+    .map(|file| {
+      let contents = fs::read(&file).unwrap();
+      let path = file.strip_prefix(root).unwrap().to_str().unwrap().replace('\\', "/");
+      (path, (hex(&Sha256::digest(&contents)), contents.len() as u64))
+    })
+    .collect()
+}
+
+// The manifest written by the last deploy, empty before the first
+pub fn read(path: &Path) -> Manifest {
+  let Ok(contents) = fs::read_to_string(path) else { return Manifest::new() };
+  contents
+    .lines()
+    .filter_map(|line| {
+      let mut fields = line.splitn(3, '\t');
+      let (hash, size, path) = (fields.next()?, fields.next()?, fields.next()?);
+      Some((path.to_string(), (hash.to_string(), size.parse().ok()?)))
+    })
+    .collect()
+}
+
+// One "hash<TAB>size<TAB>path" line per file so it diffs well in git
+pub fn write(path: &Path, manifest: &Manifest) {
+  let mut lines = String::new();
+  for (file, (hash, size)) in manifest {
+    writeln!(lines, "{hash}\t{size}\t{file}").unwrap();
+  }
+  fs::write(path, lines).unwrap();
+}
+
+// Files added, changed and removed going from the old manifest to the new,
+// in path order
+pub fn diff(old: &Manifest, new: &Manifest) -> Vec<Change> {
+  let mut changes = vec![];
+  for (path, (hash, size)) in new {
+    match old.get(path) {
+      None => changes.push(Change::Added(path.clone(), *size)),
+      Some((old_hash, old_size)) if old_hash != hash => {
+        changes.push(Change::Changed(path.clone(), *old_size, *size));
+      },
+      Some(_) => (),
+    }
+  }
+  for (path, (_, size)) in old {
+    if !new.contains_key(path) {
+      changes.push(Change::Removed(path.clone(), *size));
+    }
+  }
+  changes.sort_by(|a, b| path_of(a).cmp(path_of(b)));
+  changes
+}
+
+fn path_of(change: &Change) -> &str {
+  match change {
+    Change::Added(path, _) | Change::Changed(path, _, _) | Change::Removed(path, _) => path,
+  }
+}
+
+// Reasons not to deploy the staged output under root: draft or scheduled
+// posts, or links to them, that weren't removed, template placeholders that
+// weren't filled in and broken links
+pub fn blockers(root: &Path, sources: &Sources, posts: &[Metadata]) -> Vec<Finding> {
+  // Code samples are full of braces so only look outside of them
+  let code_re = Regex::new(r"(?s)<(pre|code|script|style)\b.*?</(pre|code|script|style)>").unwrap();
+  let placeholder_re = Regex::new(r"\{[a-z][a-z_]*\}").unwrap();
+  let drafts: Vec<&Metadata> = posts.iter().filter(|p| p.visibility().is_draft()).collect();
+  let mut findings = vec![];
+
+  for p in &drafts {
+    let file = output(root, &p.url());
+    if file.exists() {
+      let message = format!("draft or scheduled post {} in the output", p.name);
+      findings.push(Finding { file: file.to_str().unwrap().to_string(), line: 1, message });
+    }
+  }
+
+  for file in io::files_in_tree(root) {
+    if file.extension().is_none_or(|ext| ext != "html") { continue; }
+    let contents = fs::read_to_string(&file).unwrap();
+
+    for tag in html::tags(&contents).iter().filter(|t| !t.closing) {
+      let href = tag.attribute("href").unwrap_or_default();
+      if let Some(p) = drafts.iter().find(|p| p.url() == href) {
+        let message = format!("link to draft or scheduled post {}", p.name);
+        findings.push(Finding { file: file.to_str().unwrap().to_string(), line: tag.line, message });
+      }
+    }
+
+    let prose = code_re.replace_all(&contents, |caps: &regex::Captures| {
+      // Keep line numbers lined up with the file
+      "\n".repeat(caps[0].matches('\n').count())
+    });
+    for m in placeholder_re.find_iter(&prose) {
+      let line = html::line_at(&prose, m.start());
+      let message = format!("unfilled placeholder {}", m.as_str());
+      findings.push(check::locate(root, &file, m.as_str(), line, sources, message));
+    }
+  }
+
+  findings.extend(check::links(root, sources));
+  findings
+}
+
+// The file under root served at a URL
+fn output(root: &Path, url: &str) -> PathBuf {
+  let path = root.join(url.trim_start_matches('/'));
+  if url.ends_with('/') { path.join("index.html") } else { path }
+}
@@ -0,0 +1,137 @@
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::permalink;
+use crate::post::{Metadata, PathConfig};
+use crate::string::slugify;
+
+// Metadata every post starts with
+const HEADER: &str = "\
+mmd header: {{../templates/header.html}}
+mmd footer: {{../templates/footer.html}}
+css: /css/main.css
+";
+
+// Writes posts/draft-<slug>.md, or .rs using the /** */ layout rs2md expects,
+// with the standard metadata and the title as its heading. Tags default to
+// "untagged" as metadata needs a value.
+pub fn new(posts: &Path, title: &str, tags: &[String], rs: bool) -> Result<PathBuf, String> {
+  let slug = slugify(title);
+  if slug.is_empty() {
+    return Err(format!("No slug in \"{title}\""));
+  }
+
+  let path = posts.join(format!("draft-{slug}.{}", if rs { "rs" } else { "md" }));
+  if path.exists() {
+    return Err(format!("{} already exists", path.display()));
+  }
+
+  let tags = if tags.is_empty() { "untagged".to_string() } else { tags.join(" ") };
+  let mut contents = String::new();
+  if rs { contents.push_str("/**\n"); }
+  write!(contents, "{HEADER}tags: {tags}\n\n").unwrap();
+  // .md posts leave two blank lines after the metadata, .rs posts one
+  if !rs { contents.push('\n'); }
+  write!(contents, "# {title}\n\n").unwrap();
+  if rs { contents.push_str("*/\n"); }
+
+  fs::write(&path, contents).map_err(|e| format!("{}: {e}", path.display()))?;
+  Ok(path)
+}
+
+// Renames posts/draft-<name> to <today>-<name>, setting any `created:` to
+// today and adding the draft's URL to `aliases:` when the new one differs.
+// name can be given with or without the draft- prefix and extension.
+pub fn publish(
+  paths: &PathConfig,
+  name: &str,
+  today: &str,
+  pattern: &str,
+) -> Result<PathBuf, String> {
+  let stem = Path::new(name).file_stem().unwrap_or_default().to_str().unwrap();
+  let name = stem.strip_prefix("draft-").unwrap_or(stem);
+  let draft_name = format!("draft-{name}");
+  let Some(draft) =
+    ["md", "rs"]
+    .iter()
+    .map(|ext| paths.posts.join(format!("{draft_name}.{ext}")))
+    .find(|path| path.exists())
+  else {
+    return Err(format!("No draft named {draft_name} in {}", paths.posts.display()));
+  };
+
+  let published_name = format!("{today}-{name}");
+  let path = draft.with_file_name(format!(
+    "{published_name}.{}",
+    draft.extension().unwrap().to_str().unwrap(),
+  ));
+  if path.exists() {
+    return Err(format!("{} already exists", path.display()));
+  }
+
+  let contents = fs::read_to_string(&draft).map_err(|e| format!("{}: {e}", draft.display()))?;
+  let value = |key: &str| metadata_value(&contents, key).unwrap_or_default().to_string();
+  let url = |name: &str, created: &str| {
+    let p = Metadata {
+      name: name.to_string(),
+      created: created.to_string(),
+      slug: value("slug"),
+      ..Default::default()
+    };
+    permalink::expand(pattern, &p)
+  };
+  let (old_url, new_url) = (url(&draft_name, "draft"), url(&published_name, today));
+
+  let mut contents = set_metadata(&contents, "created", today, false);
+  if old_url != new_url {
+    let aliases = value("aliases");
+    let aliases = if aliases.is_empty() { old_url } else { format!("{aliases} {old_url}") };
+    contents = set_metadata(&contents, "aliases", &aliases, true);
+  }
+
+  fs::write(&path, contents).map_err(|e| format!("{}: {e}", path.display()))?;
+  fs::remove_file(&draft).map_err(|e| format!("{}: {e}", draft.display()))?;
+  // Otherwise the draft would still be built from its old artifact
+  let _ = fs::remove_file(paths.artifacts.join(format!("{draft_name}.md")));
+  Ok(path)
+}
+
+// Metadata block lines: after any opening /** of a .rs post and up to the
+// first blank line
+fn metadata_lines(contents: &str) -> impl Iterator<Item = (usize, &str)> {
+  contents
+    .lines()
+    .enumerate()
+    .skip_while(|(_, line)| line.trim_end() == "/**")
+    .take_while(|(_, line)| !line.trim().is_empty())
+}
+
+fn metadata_value<'a>(contents: &'a str, key: &str) -> Option<&'a str> {
+  metadata_lines(contents)
+    .find_map(|(_, line)| line.strip_prefix(key)?.strip_prefix(": "))
+    .map(str::trim)
+}
+
+// Replaces the value of key, or adds it to the end of the metadata when insert
+// is set
+fn set_metadata(contents: &str, key: &str, value: &str, insert: bool) -> String {
+  let lines: Vec<(usize, &str)> = metadata_lines(contents).collect();
+  let existing = lines.iter().find(|(_, line)| line.starts_with(&format!("{key}: ")));
+  let (at, replace) =
+    match (existing, lines.last()) {
+      (Some((i, _)), _) => (*i, true),
+      (None, Some((i, _))) if insert => (i + 1, false),
+      _ => return contents.to_string(),
+    };
+
+  let mut result = String::new();
+  for (i, line) in contents.lines().enumerate() {
+    if i == at {
+      writeln!(result, "{key}: {value}").unwrap();
+      if replace { continue; }
+    }
+    writeln!(result, "{line}").unwrap();
+  }
+  result
+}
@@ -0,0 +1,168 @@
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+
+use crate::html;
+use crate::io;
+use crate::markdown::{self, Line};
+use crate::path;
+use crate::post::{Metadata, PathConfig};
+use crate::string::escape_json;
+
+// How much a term counts for depending on where it appears
+const TITLE_WEIGHT: usize = 10;
+const TAGS_WEIGHT: usize = 6;
+const HEADING_WEIGHT: usize = 3;
+const BODY_WEIGHT: usize = 1;
+
+// Words too common to be worth indexing. Shipped in the index so search.js
+// drops the same ones from queries.
+pub const STOP_WORDS: [&str; 64] = [
+  "a", "about", "after", "all", "also", "am", "an", "and", "any", "are", "as",
+  "at", "be", "because", "been", "but", "by", "can", "could", "did", "do",
+  "does", "for", "from", "had", "has", "have", "he", "her", "his", "how", "i",
+  "if", "in", "into", "is", "it", "its", "just", "me", "my", "no", "not", "of",
+  "on", "or", "our", "so", "than", "that", "the", "their", "then", "there",
+  "these", "they", "this", "to", "was", "we", "were", "what", "with", "you",
+];
+
+// Writes public/search-index.json from the posts' titles, tags, headings and
+// body text, and public/search.html which queries it with js/search.js.
+// Drafts and scheduled posts are left out unless asked for, expired posts
+// always are.
+pub fn generate(posts: &[Metadata], paths: &PathConfig, drafts: bool) {
+  let posts: Vec<&Metadata> =
+    posts
+    .iter()
+    .filter(|p| {
+      let visibility = p.visibility();
+      visibility.is_public() || (drafts && visibility.is_draft())
+    })
+    .collect();
+  let sources: Vec<String> =
+    posts
+    .iter()
+    .map(|p| fs::read_to_string(path::markdown(&paths.artifacts, &p.name)).unwrap_or_default())
+    .collect();
+
+  fs::write(paths.public.join("search-index.json"), index(&posts, &sources)).unwrap();
+
+  let page = io::load_template("search").replace("{nav}", &io::load_template("nav"));
+  fs::write(paths.public.join("search.html"), page).unwrap();
+}
+
+// The index as JSON: stop words, a list of documents and for each stemmed
+// term a flat list of document number, score pairs
+pub fn index(posts: &[&Metadata], sources: &[String]) -> String {
+  let mut terms: BTreeMap<String, BTreeMap<usize, usize>> = BTreeMap::new();
+  let mut docs = vec![];
+
+  for (i, (p, source)) in posts.iter().zip(sources).enumerate() {
+    let (headings, body) = text(source);
+    let fields = [
+      (p.title.as_str(), TITLE_WEIGHT),
+      (p.tags.as_str(), TAGS_WEIGHT),
+      (headings.as_str(), HEADING_WEIGHT),
+      (body.as_str(), BODY_WEIGHT),
+    ];
+    for (text, weight) in fields {
+      for term in terms_in(text) {
+        *terms.entry(term).or_default().entry(i).or_default() += weight;
+      }
+    }
+
+    docs.push(format!(
+      "{{\"url\":\"{}\",\"title\":\"{}\",\"created\":\"{}\",\"tags\":\"{}\"}}",
+      escape_json(&p.url()),
+      escape_json(&p.title),
+      escape_json(&p.created),
+      escape_json(&p.tags),
+    ));
+  }
+
+  let mut json = String::from("{\"stop\":[");
+  json.push_str(&STOP_WORDS.map(|w| format!("\"{w}\"")).join(","));
+  write!(json, "],\"docs\":[{}],\"terms\":{{", docs.join(",")).unwrap();
+  for (n, (term, scores)) in terms.iter().enumerate() {
+    let scores: Vec<String> = scores.iter().map(|(doc, score)| format!("{doc},{score}")).collect();
+    let comma = if n == 0 { "" } else { "," };
+    write!(json, "{comma}\"{}\":[{}]", escape_json(term), scores.join(",")).unwrap();
+  }
+  json.push_str("}}\n");
+  json
+}
+
+// Stemmed terms in text with stop words removed
+pub fn terms_in(text: &str) -> Vec<String> {
+  tokens(text)
+    .into_iter()
+    .filter(|t| !STOP_WORDS.contains(&t.as_str()))
+    .map(|t| stem(&t))
+    .collect()
+}
+
+// Lowercase words and numbers. A trailing # or + is kept for F# and C++.
+pub fn tokens(text: &str) -> Vec<String> {
+  let word_re = Regex::new(r"[\p{L}\p{N}]+[#+]*").unwrap();
+  word_re
+    .find_iter(&text.to_lowercase())
+    .map(|m| m.as_str().to_string())
+    .filter(|t| t.chars().count() > 1)
+    .collect()
+}
+
+// Strips common English suffixes so "engines" finds "engine" and "running"
+// finds "run". Deliberately simple as js/search.js does the same.
+pub fn stem(word: &str) -> String {
+  let has_vowel = |s: &str| s.contains(['a', 'e', 'i', 'o', 'u', 'y']);
+  let mut word = word.to_string();
+  if word.chars().count() <= 3 || !word.chars().all(|c| c.is_ascii_lowercase()) {
+    return word;
+  }
+
+  if word.ends_with("sses") || word.ends_with("ies") {
+    word.truncate(word.len() - 2);
+    if word.ends_with('i') { word.replace_range(word.len() - 1.., "y"); }
+  } else if word.ends_with('s') && !word.ends_with("ss") && !word.ends_with("us")
+    && !word.ends_with("is") {
+    word.pop();
+  }
+
+  for suffix in ["ing", "ed", "ly"] {
+    if let Some(base) = word.strip_suffix(suffix)
+      && base.len() >= 3
+      && has_vowel(base)
+    {
+      word = base.to_string();
+      let bytes = word.as_bytes();
+      let last = bytes[bytes.len() - 1];
+      if suffix != "ly" && last == bytes[bytes.len() - 2] && !b"aeioulsz".contains(&last) {
+        word.pop();
+      }
+      break;
+    }
+  }
+
+  word
+}
+
+// Headings and prose from a post's Markdown, skipping the metadata header,
+// code blocks and transclusions
+pub fn text(source: &str) -> (String, String) {
+  let link_re = Regex::new(r"!?\[([^\]]*)\]\([^)]*\)").unwrap();
+  let (mut headings, mut body) = (String::new(), String::new());
+
+  let prose = &source[markdown::metadata_len(source)..];
+  for (kind, line) in markdown::lines(prose) {
+    let line = line.trim_end();
+    if kind != Line::Prose || line.trim_start().starts_with("{{") { continue; }
+
+    let plain = html::strip_tags(&link_re.replace_all(line, "$1"));
+    let target = if line.trim_start().starts_with('#') { &mut headings } else { &mut body };
+    target.push_str(plain.trim_start_matches('#'));
+    target.push('\n');
+  }
+
+  (headings, body)
+}
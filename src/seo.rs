@@ -0,0 +1,153 @@
+use regex::Regex;
+
+use crate::config::SiteConfig;
+use crate::post::{self, Metadata};
+use crate::string::{escape_html, escape_json};
+
+// Longest description before it gets truncated, as shown by search engines
+const DESCRIPTION_LENGTH: usize = 160;
+
+// Description, canonical URL, OpenGraph and Twitter tags plus a BlogPosting
+// JSON-LD block for a post's <head>
+pub fn post_head(p: &Metadata, site: &SiteConfig) -> String {
+  let url = absolute(&p.url(), site);
+  let description = description(p);
+  let image = if p.image.is_empty() { String::new() } else { absolute(&p.image, site) };
+  let mut head = vec![
+    meta("name", "description", &description),
+    format!("<link rel=\"canonical\" href=\"{}\">", escape_html(&url)),
+    meta("property", "og:type", "article"),
+    meta("property", "og:site_name", &site.name),
+    meta("property", "og:title", &p.title),
+    meta("property", "og:description", &description),
+    meta("property", "og:url", &url),
+  ];
+  if !image.is_empty() {
+    head.push(meta("property", "og:image", &image));
+  }
+  if post::is_date(&p.created) {
+    head.push(meta("property", "article:published_time", &p.created));
+  }
+  if post::is_date(&p.updated) {
+    head.push(meta("property", "article:modified_time", &p.updated));
+  }
+  for tag in p.tags.split_whitespace() {
+    head.push(meta("property", "article:tag", tag));
+  }
+
+  let card = if image.is_empty() { "summary" } else { "summary_large_image" };
+  head.push(meta("name", "twitter:card", card));
+  head.push(meta("name", "twitter:title", &p.title));
+  head.push(meta("name", "twitter:description", &description));
+  if !image.is_empty() {
+    head.push(meta("name", "twitter:image", &image));
+  }
+
+  let mut json = vec![
+    json_field("@context", "https://schema.org"),
+    json_field("@type", "BlogPosting"),
+    json_field("headline", &p.title),
+    json_field("description", &description),
+    json_field("url", &url),
+    json_field("mainEntityOfPage", &url),
+  ];
+  if post::is_date(&p.created) {
+    json.push(json_field("datePublished", &p.created));
+  }
+  let modified = if post::is_date(&p.updated) { &p.updated } else { &p.created };
+  if post::is_date(modified) {
+    json.push(json_field("dateModified", modified));
+  }
+  if !image.is_empty() {
+    json.push(json_field("image", &image));
+  }
+  if !p.tags.is_empty() {
+    json.push(json_field("keywords", &p.tags.split_whitespace().collect::<Vec<_>>().join(", ")));
+  }
+  json.push(format!(
+    "\"author\":{{{},{}}}",
+    json_field("@type", "Person"),
+    json_field("name", &site.author),
+  ));
+  json.push(format!(
+    "\"publisher\":{{{},{}}}",
+    json_field("@type", "Organization"),
+    json_field("name", &site.name),
+  ));
+  head.push(json_ld(&json));
+
+  head.join("\n") + "\n"
+}
+
+// Description, canonical URL, OpenGraph tags and a WebSite JSON-LD block for
+// the home page's <head>
+pub fn home_head(description: &str, site: &SiteConfig) -> String {
+  let url = absolute("/", site);
+  let description = summarise(description);
+
+  [
+    meta("name", "description", &description),
+    format!("<link rel=\"canonical\" href=\"{}\">", escape_html(&url)),
+    meta("property", "og:type", "website"),
+    meta("property", "og:site_name", &site.name),
+    meta("property", "og:title", &site.name),
+    meta("property", "og:description", &description),
+    meta("property", "og:url", &url),
+    meta("name", "twitter:card", "summary"),
+    meta("name", "twitter:title", &site.name),
+    meta("name", "twitter:description", &description),
+    json_ld(&[
+      json_field("@context", "https://schema.org"),
+      json_field("@type", "WebSite"),
+      json_field("name", &site.name),
+      json_field("url", &url),
+      json_field("description", &description),
+    ]),
+  ].join("\n") + "\n"
+}
+
+// The description metadata or failing that a plain text version of the intro
+pub fn description(p: &Metadata) -> String {
+  if p.description.is_empty() { summarise(&p.intro) } else { p.description.clone() }
+}
+
+// Strips Markdown from text and truncates it to a description's length at a
+// word boundary
+pub fn summarise(markdown: &str) -> String {
+  let link_re = Regex::new(r"!?\[([^\]]*)\]\([^)]*\)").unwrap();
+  let text = link_re.replace_all(markdown, "$1").replace(['*', '_', '`'], "");
+  let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+
+  if text.chars().count() <= DESCRIPTION_LENGTH { return text; }
+
+  let mut summary = String::new();
+  for word in text.split(' ') {
+    if summary.chars().count() + word.chars().count() + 1 >= DESCRIPTION_LENGTH {
+      break;
+    }
+    if !summary.is_empty() { summary.push(' '); }
+    summary.push_str(word);
+  }
+  summary.push('…');
+  summary
+}
+
+fn absolute(url: &str, site: &SiteConfig) -> String {
+  if url.starts_with('/') {
+    format!("{}{url}", site.base_url.trim_end_matches('/'))
+  } else {
+    url.to_string()
+  }
+}
+
+fn meta(attribute: &str, name: &str, content: &str) -> String {
+  format!("<meta {attribute}=\"{name}\" content=\"{}\">", escape_html(content))
+}
+
+fn json_field(key: &str, value: &str) -> String {
+  format!("\"{key}\":\"{}\"", escape_json(value))
+}
+
+fn json_ld(fields: &[String]) -> String {
+  format!("<script type=\"application/ld+json\">{{{}}}</script>", fields.join(","))
+}
@@ -0,0 +1,122 @@
+use std::fs;
+
+use crate::index;
+use crate::io;
+use crate::post::{Metadata, PathConfig};
+use crate::string::{escape_html, slugify};
+
+// Posts sharing `series:` metadata, in reading order
+pub struct Series<'a> {
+  pub name: String,
+  pub parts: Vec<&'a Metadata>,
+}
+
+impl Series<'_> {
+  pub fn url(&self) -> String {
+    url(&self.name)
+  }
+}
+
+pub fn url(name: &str) -> String {
+  format!("/series/{}.html", slugify(name))
+}
+
+// Every series, by name. Parts are ordered by `series_order:` then by date
+// with only published posts included.
+pub fn all(posts: &[Metadata]) -> Vec<Series<'_>> {
+  let mut series: Vec<Series> = vec![];
+  for p in posts.iter().filter(|p| !p.series.is_empty() && p.visibility().is_public()) {
+    match series.iter_mut().find(|s| s.name == p.series) {
+      Some(s) => s.parts.push(p),
+      None => series.push(Series { name: p.series.clone(), parts: vec![p] }),
+    }
+  }
+
+  for s in &mut series {
+    s.parts.sort_by(|a, b| {
+      let order = |p: &Metadata| p.series_order.unwrap_or(usize::MAX);
+      order(a).cmp(&order(b)).then_with(|| a.created.cmp(&b.created)).then_with(|| a.name.cmp(&b.name))
+    });
+  }
+  series.sort_by(|a, b| a.name.cmp(&b.name));
+  series
+}
+
+// Sets the part number of each post in a series, used on the home page cards
+pub fn number(posts: &mut [Metadata]) {
+  let parts: Vec<(String, usize)> =
+    all(posts)
+    .iter()
+    .flat_map(|s| s.parts.iter().enumerate().map(|(i, p)| (p.name.clone(), i + 1)))
+    .collect();
+
+  for (name, part) in parts {
+    if let Some(p) = posts.iter_mut().find(|p| p.name == name) {
+      p.part = part;
+    }
+  }
+}
+
+// A box listing all parts of the post's series with previous and next links,
+// empty for posts not in a series
+pub fn navigation(p: &Metadata, posts: &[Metadata]) -> String {
+  let all = all(posts);
+  let Some(series) = all.iter().find(|s| s.name == p.series) else { return String::new() };
+  let Some(at) = series.parts.iter().position(|part| part.name == p.name) else {
+    return String::new();
+  };
+
+  let parts: Vec<String> = series.parts.iter().map(|part| {
+    if part.name == p.name {
+      format!("<li aria-current=\"page\">{}</li>", escape_html(&part.title))
+    } else {
+      format!("<li><a href=\"{}\">{}</a></li>", part.url(), escape_html(&part.title))
+    }
+  }).collect();
+
+  let mut links = vec![];
+  if at > 0 {
+    let prev = series.parts[at - 1];
+    links.push(format!(
+      "<a rel=\"prev\" href=\"{}\">← Previous: {}</a>",
+      prev.url(),
+      escape_html(&prev.title),
+    ));
+  }
+  if let Some(next) = series.parts.get(at + 1) {
+    links.push(format!(
+      "<a rel=\"next\" href=\"{}\">Next: {} →</a>",
+      next.url(),
+      escape_html(&next.title),
+    ));
+  }
+
+  format!(
+    "<aside class=\"series\">\n<p>Part {} of {} in <a href=\"{}\">{}</a></p>\n<ol>\n{}\n</ol>\n<p class=\"prev-next\">{}</p>\n</aside>\n",
+    at + 1,
+    series.parts.len(),
+    series.url(),
+    escape_html(&series.name),
+    parts.join("\n"),
+    links.join("\n"),
+  )
+}
+
+// Writes an overview page of cards for each series to public/series/
+pub fn generate(posts: &[Metadata], paths: &PathConfig) {
+  let dir = paths.public.join("series");
+  if dir.exists() {
+    fs::remove_dir_all(&dir).unwrap();
+  }
+  fs::create_dir_all(&dir).unwrap();
+
+  let template = io::load_template("series").replace("{nav}", &io::load_template("nav"));
+  for series in all(posts) {
+    let cards: Vec<String> = series.parts.iter().map(|p| index::card(p)).collect();
+    let html =
+      template
+      .replace("{name}", &escape_html(&series.name))
+      .replace("{posts}", &cards.join("\n"));
+    fs::write(dir.join(format!("{}.html", slugify(&series.name))), html).unwrap();
+  }
+}
@@ -0,0 +1,143 @@
+use regex::Regex;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use crate::config::SiteConfig;
+use crate::io;
+use crate::post::{self, Metadata, PathConfig};
+use crate::string::escape_html;
+
+// Protocol limits for a single sitemap file
+pub const MAX_URLS: usize = 50_000;
+pub const MAX_BYTES: usize = 50 * 1024 * 1024;
+
+const XMLNS: &str = "http://www.sitemaps.org/schemas/sitemap/0.9";
+
+pub struct Entry {
+  pub loc: String,
+  pub lastmod: String,
+}
+
+// Generates sitemap.xml and robots.txt from posts and pages. Unpublished posts
+// and noindex pages are left out of the sitemap, noindex pages are disallowed.
+pub fn generate(
+  posts: &[Metadata],
+  pages: &[Metadata],
+  paths: &PathConfig,
+  site: &SiteConfig,
+) {
+  let base = site.base_url.trim_end_matches('/');
+  let entries = entries(posts, pages, base);
+  write(&entries, &paths.public, base, MAX_URLS);
+
+  let disallowed: Vec<String> =
+    posts
+    .iter()
+    .filter(|p| p.noindex)
+    .map(Metadata::url)
+    .chain(pages.iter().filter(|p| p.noindex).map(page_url))
+    .map(|url| format!("Disallow: {url}\n"))
+    .collect();
+  let disallowed =
+    if disallowed.is_empty() { "Disallow:\n".to_string() } else { disallowed.concat() };
+
+  fs::write(
+    paths.public.join("robots.txt"),
+    format!("User-agent: *\n{disallowed}\nSitemap: {base}/sitemap.xml\n"),
+  ).unwrap();
+}
+
+pub fn entries(posts: &[Metadata], pages: &[Metadata], base: &str) -> Vec<Entry> {
+  let posts: Vec<Entry> =
+    posts
+    .iter()
+    .filter(|p| !p.noindex && p.visibility().is_public())
+    .map(|p| Entry { loc: format!("{base}{}", p.url()), lastmod: lastmod(p) })
+    .collect();
+  let pages =
+    pages
+    .iter()
+    .filter(|p| !p.noindex)
+    .map(|p| Entry { loc: format!("{base}{}", page_url(p)), lastmod: lastmod(p) });
+
+  let home = Entry {
+    loc: format!("{base}/"),
+    lastmod: posts.iter().map(|e| e.lastmod.clone()).max().unwrap_or_default(),
+  };
+
+  std::iter::once(home).chain(posts).chain(pages).collect()
+}
+
+// Writes sitemap.xml, or when the entries don't fit in one file, a sitemap
+// index pointing at sitemap-1.xml, sitemap-2.xml...
+pub fn write(entries: &[Entry], root: &Path, base: &str, max_urls: usize) {
+  let numbered = Regex::new(r"^sitemap-\d+\.xml$").unwrap();
+  for file in io::paths_in_dir(root, &["xml"]) {
+    if numbered.is_match(file.file_name().unwrap().to_str().unwrap()) {
+      fs::remove_file(file).unwrap();
+    }
+  }
+
+  let mut chunks: Vec<Vec<String>> = vec![vec![]];
+  let mut bytes = urlset(&[]).len();
+  for entry in entries {
+    let url = url_xml(entry);
+    let chunk = chunks.last_mut().unwrap();
+    if chunk.len() == max_urls || bytes + url.len() > MAX_BYTES {
+      chunks.push(vec![]);
+      bytes = urlset(&[]).len();
+    }
+    bytes += url.len();
+    chunks.last_mut().unwrap().push(url);
+  }
+
+  if chunks.len() == 1 {
+    fs::write(root.join("sitemap.xml"), urlset(&chunks[0])).unwrap();
+    return;
+  }
+
+  let mut sitemaps = String::new();
+  for (i, chunk) in chunks.iter().enumerate() {
+    let name = format!("sitemap-{}.xml", i + 1);
+    fs::write(root.join(&name), urlset(chunk)).unwrap();
+    writeln!(sitemaps, "  <sitemap><loc>{base}/{name}</loc></sitemap>").unwrap();
+  }
+  fs::write(
+    root.join("sitemap.xml"),
+    format!("{}<sitemapindex xmlns=\"{XMLNS}\">\n{sitemaps}</sitemapindex>\n", header()),
+  ).unwrap();
+}
+
+fn header() -> &'static str {
+  "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"
+}
+
+fn urlset(urls: &[String]) -> String {
+  format!("{}<urlset xmlns=\"{XMLNS}\">\n{}</urlset>\n", header(), urls.concat())
+}
+
+fn url_xml(entry: &Entry) -> String {
+  let lastmod =
+    if entry.lastmod.is_empty() {
+      String::new()
+    } else {
+      format!("<lastmod>{}</lastmod>", entry.lastmod)
+    };
+
+  format!("  <url><loc>{}</loc>{lastmod}</url>\n", escape_html(&entry.loc))
+}
+
+fn page_url(page: &Metadata) -> String {
+  format!("/{}.html", page.name)
+}
+
+// Updated date, or created when never updated. Only real dates count as
+// created falls back to the filename for undated files.
+fn lastmod(p: &Metadata) -> String {
+  [&p.updated, &p.created]
+    .into_iter()
+    .find(|d| post::is_date(d))
+    .cloned()
+    .unwrap_or_default()
+}
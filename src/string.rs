@@ -1,3 +1,4 @@
+use std::fmt::Write;
 
 pub fn format_or_empty(label: &str, value: &String) -> String {
   if value.is_empty() { return String::new()}
@@ -5,3 +6,92 @@ pub fn format_or_empty(label: &str, value: &String) -> String {
   format!("{label}{value}")
 }
 
+// Escapes text for use in HTML content and attribute values
+pub fn escape_html(s: &str) -> String {
+  s.replace('&', "&amp;")
+    .replace('"', "&quot;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+}
+
+// Escapes text for use inside a JSON string
+pub fn escape_json(s: &str) -> String {
+  let mut escaped = String::new();
+  for c in s.chars() {
+    match c {
+      '"' => escaped.push_str("\\\""),
+      '\\' => escaped.push_str("\\\\"),
+      '\n' => escaped.push_str("\\n"),
+      '\r' => escaped.push_str("\\r"),
+      '\t' => escaped.push_str("\\t"),
+      '<' => escaped.push_str("\\u003c"),
+      c if c.is_control() => write!(escaped, "\\u{:04x}", c as u32).unwrap(),
+      c => escaped.push(c),
+    }
+  }
+  escaped
+}
+
+// Lowercase hex encoding of bytes
+pub fn hex(bytes: &[u8]) -> String {
+  bytes.iter().fold(String::new(), |mut hex, b| {
+    write!(hex, "{b:02x}").unwrap();
+    hex
+  })
+}
+
+// Replaces whatever sits between <!-- name --> and <!-- /name --> with content,
+// adding the markers before the last occurrence of anchor the first time.
+// Empty content removes the section altogether.
+pub fn replace_section(html: &str, name: &str, content: &str, anchor: &str) -> String {
+  let start = format!("<!-- {name} -->");
+  let end = format!("<!-- /{name} -->");
+  let mut html = html.to_string();
+
+  if let Some(from) = html.find(&start)
+    && let Some(to) = html[from..].find(&end)
+  {
+    let newline = usize::from(html[from + to + end.len()..].starts_with('\n'));
+    html.replace_range(from..from + to + end.len() + newline, "");
+  }
+
+  if content.is_empty() { return html; }
+
+  match html.rfind(anchor) {
+    Some(at) => {
+      html.insert_str(at, &format!("{start}\n{content}{end}\n"));
+      html
+    },
+    None => html,
+  }
+}
+
+// Lowercase words joined by hyphens for use in URLs. F# and C# keep their
+// sharps as words.
+pub fn slugify(s: &str) -> String {
+  s.replace('#', "sharp")
+    .to_lowercase()
+    .split(|c: char| !c.is_alphanumeric())
+    .filter(|word| !word.is_empty())
+    .collect::<Vec<_>>()
+    .join("-")
+}
+
+// Standard, padded base64 encoding of bytes
+pub fn base64(bytes: &[u8]) -> String {
+  const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+  let mut encoded = String::new();
+
+  for chunk in bytes.chunks(3) {
+    let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+    let n = u32::from(b[0]) << 16 | u32::from(b[1]) << 8 | u32::from(b[2]);
+    for i in 0..4 {
+      if i <= chunk.len() {
+        encoded.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+      } else {
+        encoded.push('=');
+      }
+    }
+  }
+  encoded
+}
@@ -0,0 +1,64 @@
+use regex::Regex;
+use std::fmt;
+
+use crate::markdown;
+
+// - [ ] todo, - [x] done, with any list marker and indent
+const TASK: &str = r"^(\s*(?:[-*+]|\d+\.)\s+)\[([ xX])\](\s|$)";
+
+// Placed in a page to show its progress
+pub const DIRECTIVE: &str = "{progress}";
+
+// Tasks done out of the total in a document
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Progress {
+  pub done: usize,
+  pub total: usize,
+}
+
+impl fmt::Display for Progress {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}/{} done", self.done, self.total)
+  }
+}
+
+impl Progress {
+  // A bar with the count beside it
+  pub fn html(&self) -> String {
+    format!(
+      "<span class=\"progress\"><progress value=\"{}\" max=\"{}\">{self}</progress> {self}</span>",
+      self.done,
+      self.total,
+    )
+  }
+}
+
+// Counts the task list items outside fenced code
+pub fn progress(markdown: &str) -> Progress {
+  let task_re = Regex::new(TASK).unwrap();
+  let mut progress = Progress::default();
+
+  for caps in markdown::mask(markdown, true).lines().filter_map(|line| task_re.captures(line)) {
+    progress.total += 1;
+    if &caps[2] != " " { progress.done += 1; }
+  }
+
+  progress
+}
+
+// Turns task list items into disabled checkboxes and fills in {progress}
+// outside inline code
+pub fn from_markdown(prose: &str, progress: Progress) -> String {
+  let task_re = Regex::new(TASK).unwrap();
+  prose
+    .split_inclusive('\n')
+    .map(|line| {
+      let line =
+        task_re.replace(line, |caps: &regex::Captures| {
+          let checked = if &caps[2] == " " { "" } else { " checked" };
+          format!("{}<input type=\"checkbox\" class=\"task\" disabled{checked}>{}", &caps[1], &caps[3])
+        });
+      markdown::outside_inline_code(&line, |text| text.replace(DIRECTIVE, &progress.html()))
+    })
+    .collect()
+}
@@ -0,0 +1,125 @@
+use regex::Regex;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::check::Finding;
+use crate::html;
+use crate::markdown;
+use crate::permalink;
+use crate::post::Metadata;
+use crate::string::escape_html;
+
+fn link_re() -> Regex {
+  Regex::new(r"\[\[([^\[\]|]+)(?:\|([^\[\]]+))?\]\]").unwrap()
+}
+
+// Replaces [[slug]] and [[slug|text]] with Markdown links to the post, titled
+// with its title unless text is given. Slugs are matched against each post's
+// `slug:` metadata or its filename with or without the date. Links that don't
+// resolve are left as they are for unresolved to report.
+pub fn from_markdown(markdown: &str, posts: &[Metadata]) -> String {
+  let link_re = link_re();
+  markdown.split_inclusive('\n').map(|line| markdown::outside_inline_code(line, |text| {
+    link_re.replace_all(text, |caps: &regex::Captures| {
+      match resolve(posts, caps[1].trim()) {
+        Some(p) => {
+          let text = caps.get(2).map_or(p.title.as_str(), |m| m.as_str().trim());
+          format!("[{text}]({})", p.url())
+        },
+        None => caps[0].to_string(),
+      }
+    }).to_string()
+  })).collect()
+}
+
+// The published post a link is to. Drafts, scheduled and expired posts
+// aren't there to link to in production.
+pub fn resolve<'a>(posts: &'a [Metadata], slug: &str) -> Option<&'a Metadata> {
+  posts.iter().find(|p| names(p, slug) && p.visibility().is_public())
+}
+
+// Every post a wiki link in the Markdown names, published or not
+pub fn linked<'a>(posts: &'a [Metadata], markdown: &str) -> Vec<&'a Metadata> {
+  let targets = links(markdown, &link_re());
+  posts.iter().filter(|p| targets.iter().any(|(t, _)| names(p, t))).collect()
+}
+
+fn names(p: &Metadata, slug: &str) -> bool {
+  p.name == slug || permalink::slug(p) == slug
+}
+
+// Every wiki link in the Markdown files that doesn't name a published post
+pub fn unresolved(posts: &[Metadata], files: &[PathBuf]) -> Vec<Finding> {
+  let link_re = link_re();
+  let mut findings = vec![];
+
+  for file in files {
+    let Ok(contents) = fs::read_to_string(file) else { continue };
+    for (target, at) in links(&contents, &link_re) {
+      if resolve(posts, &target).is_some() { continue; }
+
+      let message =
+        match posts.iter().find(|p| names(p, &target)) {
+          Some(p) => format!("link [[{target}]] to unpublished post {}", p.name),
+          None => format!("unresolved link [[{target}]]"),
+        };
+      findings.push(Finding {
+        file: file.to_str().unwrap().to_string(),
+        line: html::line_at(&contents, at),
+        message,
+      });
+    }
+  }
+
+  findings
+}
+
+// For each post, the published posts linking to it, newest first
+pub fn backlinks<'a>(posts: &'a [Metadata], sources: &[String]) -> Vec<Vec<&'a Metadata>> {
+  let link_re = link_re();
+  let targets: Vec<Vec<String>> =
+    sources
+    .iter()
+    .map(|source| links(source, &link_re).into_iter().map(|(target, _)| target).collect())
+    .collect();
+
+  posts.iter().map(|p| {
+    posts
+      .iter()
+      .zip(&targets)
+      .filter(|(other, _)| other.name != p.name && other.visibility().is_public())
+      .filter(|(_, targets)| targets.iter().any(|t| resolve(posts, t).is_some_and(|r| r.name == p.name)))
+      .map(|(other, _)| other)
+      .collect()
+  }).collect()
+}
+
+pub fn render(backlinks: &[&Metadata]) -> String {
+  if backlinks.is_empty() { return String::new(); }
+
+  let items: Vec<String> =
+    backlinks
+    .iter()
+    .map(|p| format!("<li><a href=\"{}\">{}</a></li>", p.url(), escape_html(&p.title)))
+    .collect();
+  format!(
+    "<section class=\"backlinks\">\n<h2>Referenced by</h2>\n<ul>\n{}\n</ul>\n</section>\n",
+    items.join("\n"),
+  )
+}
+
+// Targets of the wiki links in Markdown with their offsets, skipping fenced
+// and inline code
+fn links(markdown: &str, link_re: &Regex) -> Vec<(String, usize)> {
+  let mut found = vec![];
+  let mut offset = 0;
+
+  for line in markdown::mask(markdown, false).split_inclusive('\n') {
+    found.extend(link_re.captures_iter(line).map(|caps| {
+      (caps[1].trim().to_string(), offset + caps.get(0).unwrap().start())
+    }));
+    offset += line.len();
+  }
+
+  found
+}
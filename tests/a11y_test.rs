@@ -0,0 +1,132 @@
+use std::fs;
+use std::path::Path;
+
+use crate::common::*;
+use website::a11y;
+use website::check;
+use website::post::Metadata;
+
+#[test]
+fn page_reports_missing_alt_empty_links_headings_and_duplicate_ids() {
+  let html = r#"<!DOCTYPE html>
+<html>
+<head><title> </title></head>
+<body>
+<h1>Title</h1>
+<a href="/github"><img src="/images/github.svg"></a>
+<a href="/top" title="Jump to top"><img src="/images/top.svg" alt=""></a>
+<a href="/about"><img src="/images/about.svg" alt="About"></a>
+<h3 id="intro">Intro</h3>
+<h2 id="intro">Again</h2>
+</body>
+</html>
+"#;
+
+  let problems = a11y::page(html);
+
+  let messages: Vec<(usize, &str)> =
+    problems.iter().map(|(_, line, message)| (*line, message.as_str())).collect();
+  assert_eq!(messages, [
+    (2, "<html> missing lang"),
+    (1, "missing or empty <title>"),
+    (1, "missing <meta charset>"),
+    (6, "empty link text (/github)"),
+    (6, "<img> missing alt text (/images/github.svg)"),
+    (9, "heading level skipped (h1 to h3)"),
+    (10, "duplicate id intro"),
+  ]);
+}
+
+#[test]
+fn page_accepts_an_accessible_page() {
+  let html = r#"<html lang="en"><head><meta charset="utf-8"><title>Home</title></head>
+<body><h1>Home</h1><h2>Posts</h2><h3>First</h3><h2>More</h2></body></html>"#;
+
+  assert!(a11y::page(html).is_empty());
+}
+
+#[test]
+fn contrast_ratio_matches_wcag() {
+  assert_eq!(format!("{:.1}", a11y::contrast_ratio("#000", "#ffffff")), "21.0");
+  assert_eq!(format!("{:.2}", a11y::contrast_ratio("#777777", "#fff")), "4.48");
+}
+
+#[test]
+fn contrast_reports_text_colours_too_close_to_backgrounds() {
+  let css = ":root {
+  --bg: #1f242a;
+  --text: #d3d4d6;
+  --muted: #5C6773;
+}
+body { background-color: var(--bg); color: var(--text); }
+.dates { color: var(--muted); }
+";
+
+  let findings = a11y::contrast(css, Path::new("main.css"));
+
+  assert_eq!(findings.len(), 1);
+  assert_eq!(findings[0].line, 4);
+  assert!(findings[0].message.starts_with("low contrast"));
+  assert!(findings[0].message.contains("--muted (#5C6773) on --bg (#1f242a)"));
+}
+
+#[test]
+fn contrast_only_pairs_colours_set_on_the_same_or_enclosing_rules() {
+  let css = ":root {
+  --dark: #111111;
+  --light: #ffffff;
+}
+body { background-color: var(--light); color: var(--dark); }
+.card {
+  background: var(--dark);
+  --accent: var(--light);
+  h3 { color: var(--accent); }
+}
+";
+
+  assert!(a11y::contrast(css, Path::new("main.css")).is_empty());
+}
+
+#[test]
+fn contrast_checks_theme_overrides_in_their_own_scope() {
+  let css = ":root {
+  --bg: #ffffff;
+  --text: #111111;
+}
+@media (prefers-color-scheme: dark) {
+  :root {
+    --bg: #222222;
+  }
+}
+body { background-color: var(--bg); color: var(--text); }
+";
+
+  let findings = a11y::contrast(css, Path::new("main.css"));
+
+  assert_eq!(findings.len(), 1);
+  assert_eq!(findings[0].line, 3);
+  assert!(findings[0].message.contains("--text (#111111) on --bg (#222222)"));
+  assert!(findings[0].message.ends_with("in @media (prefers-color-scheme: dark) :root"));
+}
+
+#[test]
+fn lint_locates_findings_in_the_source_post() {
+  let dirs = setup();
+  let paths = dirs.as_path_config();
+  fs::write(
+    paths.posts.join(MD_FILENAME),
+    "tags: game\n\n# Title\n\n![](/images/demo.webp)\n",
+  ).unwrap();
+  let post = Metadata { name: "2020-01-01-test".to_string(), ..Default::default() };
+  fs::write(
+    paths.public_posts.join(HTML_FILENAME),
+    r#"<html lang="en"><head><meta charset="utf-8"><title>Title</title></head>
+<body><p><img src="/images/demo.webp"></p></body></html>"#,
+  ).unwrap();
+
+  let findings = a11y::lint(&paths.public, &check::sources(&[post], &paths));
+
+  assert_eq!(findings.len(), 1);
+  assert_eq!(findings[0].file, paths.posts.join(MD_FILENAME).to_str().unwrap());
+  assert_eq!(findings[0].line, 5);
+}
@@ -0,0 +1,77 @@
+use std::fs;
+
+use crate::common::*;
+use crate::refute;
+use website::archive;
+use website::post::Metadata;
+
+fn post(name: &str) -> Metadata {
+  let created = if name.starts_with("draft-") { String::new() } else { name[..10].to_string() };
+  Metadata { name: name.to_string(), title: format!("Title {name}"), created, ..Default::default() }
+}
+
+// Newest first as returned by post::build_all
+fn posts() -> Vec<Metadata> {
+  vec![
+    post("draft-typing"),
+    post("2021-02-26-neovim"),
+    post("2021-02-15-recap"),
+    post("2019-11-08-opengl"),
+    post("2019-11-06-reboot"),
+    post("2019-10-28-aws"),
+  ]
+}
+
+#[test]
+fn neighbours_skip_drafts() {
+  let posts = posts();
+
+  let (older, newer) = archive::neighbours(&posts[1], &posts);
+  assert_eq!(older.unwrap().name, "2021-02-15-recap");
+  assert!(newer.is_none());
+
+  let (older, newer) = archive::neighbours(&posts[5], &posts);
+  assert!(older.is_none());
+  assert_eq!(newer.unwrap().name, "2019-11-06-reboot");
+
+  assert_eq!(archive::neighbours(&posts[0], &posts), (None, None));
+}
+
+#[test]
+fn navigation_links_older_and_newer_posts() {
+  let posts = posts();
+
+  let html = archive::navigation(&posts[3], &posts);
+
+  assert!(html.contains("<a class=\"older\" href=\"/posts/2019-11-06-reboot.html\">"));
+  assert!(html.contains("<a class=\"newer\" href=\"/posts/2021-02-15-recap.html\">"));
+  assert_eq!(archive::navigation(&posts[0], &posts), "");
+}
+
+#[test]
+fn years_groups_dated_posts_newest_first() {
+  let posts = posts();
+
+  let years: Vec<(String, usize)> =
+    archive::years(&posts).iter().map(|(year, posts)| (year.clone(), posts.len())).collect();
+
+  assert_eq!(years, [("2021".to_string(), 2), ("2019".to_string(), 3)]);
+}
+
+#[test]
+fn generate_writes_archive_and_year_pages() {
+  let dirs = setup();
+  let paths = dirs.as_path_config();
+
+  archive::generate(&posts(), &paths);
+
+  let html = fs::read_to_string(paths.public.join("archive.html")).unwrap();
+  assert!(html.contains("<h2><a href=\"/archive/2019.html\">2019</a></h2>\n<h3>November</h3>"));
+  assert!(html.contains("<h3>October</h3>"));
+  refute!(html.contains("draft-typing"));
+
+  let html = fs::read_to_string(paths.public.join("archive/2021.html")).unwrap();
+  assert!(html.contains("<title>2021 - Electric Visions</title>"));
+  assert!(html.contains("<li><a href=\"/posts/2021-02-26-neovim.html\">Title 2021-02-26-neovim</a>"));
+  refute!(html.contains("2019"));
+}
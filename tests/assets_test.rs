@@ -0,0 +1,121 @@
+use std::fs;
+use tempfile::TempDir;
+
+use crate::refute;
+use website::assets;
+
+fn make_site() -> TempDir {
+  let root = TempDir::with_prefix("dist").unwrap();
+  let path = root.path();
+  for dir in ["css", "fonts", "images", "posts"] {
+    fs::create_dir(path.join(dir)).unwrap();
+  }
+
+  fs::write(path.join("css/main.css"), "@import url('fonts.css');\n").unwrap();
+  fs::write(
+    path.join("css/fonts.css"),
+    "src: url('/fonts/Cascadia.woff') format('woff');\n",
+  ).unwrap();
+  fs::write(path.join("fonts/Cascadia.woff"), "font").unwrap();
+  fs::write(path.join("images/cube.webp"), "image").unwrap();
+  fs::write(path.join("posts/test.html"), r#"<html>
+<link rel="stylesheet" href="/css/main.css">
+<img src="/images/cube.webp?v=1" alt="Cubes">
+<a href="https://github.com/PhilT">GitHub</a>
+<a href="/about.html">About</a>
+<meta property="og:image" content="https://example.com/images/cube.webp">
+</html>
+"#).unwrap();
+
+  root
+}
+
+#[test]
+fn hashed_name_inserts_hash_before_extension() {
+  let name = assets::hashed_name("highlightjs/highlight.min.js", b"hljs");
+
+  let (stem, ext) = name.rsplit_once('.').unwrap();
+  assert!(stem.starts_with("highlightjs/highlight.min."));
+  assert_eq!(ext, "js");
+  assert_eq!(name.len(), "highlightjs/highlight.min..js".len() + 10);
+}
+
+#[test]
+fn fingerprint_renames_assets_and_writes_manifest() {
+  let root = make_site();
+  let path = root.path();
+
+  let manifest = assets::fingerprint(path, "https://example.com");
+
+  for key in ["css/main.css", "css/fonts.css", "fonts/Cascadia.woff"] {
+    refute!(path.join(key).exists());
+    assert!(path.join(&manifest[key]).exists());
+  }
+  let json = fs::read_to_string(path.join("manifest.json")).unwrap();
+  assert!(json.contains(&format!("\"css/main.css\": \"{}\"", manifest["css/main.css"])));
+}
+
+#[test]
+fn fingerprint_rewrites_references_in_html_and_css() {
+  let root = make_site();
+  let path = root.path();
+
+  let manifest = assets::fingerprint(path, "https://example.com");
+
+  let html = fs::read_to_string(path.join("posts/test.html")).unwrap();
+  assert!(html.contains(&format!("href=\"/{}\"", manifest["css/main.css"])));
+  assert!(html.contains(&format!("src=\"/{}?v=1\"", manifest["images/cube.webp"])));
+  assert!(html.contains("href=\"https://github.com/PhilT\""));
+  assert!(html.contains("href=\"/about.html\""));
+  assert!(html.contains(&format!("content=\"https://example.com/{}\"", manifest["images/cube.webp"])));
+
+  let fonts = manifest["css/fonts.css"].trim_start_matches("css/");
+  let main = fs::read_to_string(path.join(&manifest["css/main.css"])).unwrap();
+  assert_eq!(main, format!("@import url('{fonts}');\n"));
+
+  let font = &manifest["fonts/Cascadia.woff"];
+  let css = fs::read_to_string(path.join(&manifest["css/fonts.css"])).unwrap();
+  assert!(css.contains(&format!("url('/{font}')")));
+}
+
+#[test]
+fn fingerprint_rewrites_srcset_candidates_and_inline_styles() {
+  let root = make_site();
+  let path = root.path();
+  fs::write(path.join("images/cube-2x.webp"), "large image").unwrap();
+  fs::write(path.join("posts/srcset.html"), r#"<html>
+<img srcset="/images/cube.webp 1x, https://example.com/images/cube-2x.webp 2x" alt="Cubes">
+<div style="background: url('/images/cube.webp')"></div>
+<style>.hero { background: url("https://example.com/images/cube.webp"); }</style>
+<script type="application/ld+json">{"image":"https://example.com/images/cube.webp"}</script>
+</html>
+"#).unwrap();
+
+  let manifest = assets::fingerprint(path, "https://example.com");
+
+  let html = fs::read_to_string(path.join("posts/srcset.html")).unwrap();
+  let cube = &manifest["images/cube.webp"];
+  let large = &manifest["images/cube-2x.webp"];
+  assert!(html.contains(&format!("srcset=\"/{cube} 1x, https://example.com/{large} 2x\"")));
+  assert!(html.contains(&format!("style=\"background: url('/{cube}')\"")));
+  assert!(html.contains(&format!("url(\"https://example.com/{cube}\")")));
+  assert!(html.contains(&format!("{{\"image\":\"https://example.com/{cube}\"}}")));
+}
+
+#[test]
+fn fingerprint_leaves_text_and_code_blocks_unchanged() {
+  let root = make_site();
+  let path = root.path();
+  let page = r#"<html>
+<p>Styles live in /css/main.css, served from https://example.com/css/main.css.</p>
+<pre><code>&lt;link href="/css/main.css"&gt;
+background: url('/images/cube.webp');
+</code></pre>
+</html>
+"#;
+  fs::write(path.join("posts/code.html"), page).unwrap();
+
+  assets::fingerprint(path, "https://example.com");
+
+  assert_eq!(fs::read_to_string(path.join("posts/code.html")).unwrap(), page);
+}
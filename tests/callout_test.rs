@@ -0,0 +1,30 @@
+use website::callout;
+
+#[test]
+fn from_markdown_converts_github_alerts() {
+  let markdown = "Intro\n\n> [!WARNING]\n> Mind the `unwrap`.\n>\n> Really.\n\nAfter\n";
+
+  let actual = callout::from_markdown(markdown);
+
+  assert_eq!(actual, "Intro\n\n\
+<aside class=\"callout warning\" role=\"note\">\n<p class=\"callout-title\">Warning</p>\n\n\
+Mind the `unwrap`.\n\nReally.\n\n</aside>\n\n\nAfter\n");
+}
+
+#[test]
+fn from_markdown_converts_fenced_callouts_with_titles_and_code() {
+  let markdown = ":::tip Use <clippy>\nRun it:\n\n```\n:::\n```\n:::\n";
+
+  let actual = callout::from_markdown(markdown);
+
+  assert_eq!(actual, "\
+<aside class=\"callout tip\" role=\"note\">\n<p class=\"callout-title\">Use &lt;clippy&gt;</p>\n\n\
+Run it:\n\n```\n:::\n```\n\n</aside>\n\n");
+}
+
+#[test]
+fn from_markdown_leaves_quotes_code_and_unknown_kinds_alone() {
+  let markdown = "> A quote\n\n> [!SHRUG]\n> Hmm\n\n```\n> [!NOTE]\n```\n:::aside\n";
+
+  assert_eq!(callout::from_markdown(markdown), markdown);
+}
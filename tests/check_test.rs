@@ -0,0 +1,99 @@
+use std::fs;
+
+use crate::common::*;
+use website::check::{self, Sources};
+use website::post::Metadata;
+
+#[test]
+fn links_reports_broken_references_in_html_and_css() {
+  let dirs = setup();
+  let paths = dirs.as_path_config();
+  fs::create_dir(paths.public.join("css")).unwrap();
+  fs::write(paths.public.join("about.html"), "<h1 id=\"about\">About</h1>").unwrap();
+  fs::write(paths.public.join("css/main.css"), "@import url('fonts.css');").unwrap();
+  let html = paths.public_posts.join(HTML_FILENAME);
+  fs::write(&html, r##"<link rel="stylesheet" href="/css/main.css">
+<a href="/about.html#about">About</a>
+<a href="#top">Top</a>
+<a href="https://github.com/PhilT">GitHub</a>
+<img src="/images/missing.webp">
+<a href="/about.html#team">Team</a>
+<h2 id="top">Top</h2>
+"##).unwrap();
+
+  let findings = check::links(&paths.public, &Sources::new());
+
+  let messages: Vec<&str> = findings.iter().map(|f| f.message.as_str()).collect();
+  assert_eq!(messages, [
+    "broken reference fonts.css",
+    "broken reference /images/missing.webp",
+    "missing anchor #team in /about.html#team",
+  ]);
+  assert_eq!(findings[1].file, html.to_str().unwrap());
+  assert_eq!(findings[1].line, 5);
+}
+
+#[test]
+fn links_locates_findings_in_the_source_post() {
+  let dirs = setup();
+  let paths = dirs.as_path_config();
+  fs::write(
+    paths.posts.join(MD_FILENAME),
+    "tags: game\n\n# Title\n\nSee [the demo](/posts/2018-01-01-demo.html)\n",
+  ).unwrap();
+  fs::write(
+    paths.public_posts.join(HTML_FILENAME),
+    "<p>See <a href=\"/posts/2018-01-01-demo.html\">the demo</a></p>",
+  ).unwrap();
+
+  let post = Metadata { name: "2020-01-01-test".to_string(), ..Default::default() };
+  let findings = check::links(&paths.public, &check::sources(&[post], &paths));
+
+  assert_eq!(findings.len(), 1);
+  assert_eq!(findings[0].file, paths.posts.join(MD_FILENAME).to_str().unwrap());
+  assert_eq!(findings[0].line, 5);
+  assert_eq!(
+    findings[0].to_string(),
+    format!("{}:5: broken reference /posts/2018-01-01-demo.html", findings[0].file),
+  );
+}
+
+#[test]
+fn sources_maps_permalinks_and_pages_to_their_sources() {
+  let dirs = setup();
+  let paths = dirs.as_path_config();
+  fs::write(paths.pages.join("about.md"), "title: About\n").unwrap();
+  let post = Metadata {
+    name: "2020-01-01-test".to_string(),
+    permalink: "/posts/2020/test/".to_string(),
+    ..Default::default()
+  };
+
+  let sources = check::sources(&[post], &paths);
+
+  let posts_dir = paths.public_posts.strip_prefix(&paths.public).unwrap();
+  assert_eq!(
+    sources[&posts_dir.join("2020/test/index.html")],
+    [paths.posts.join(MD_FILENAME), paths.posts.join("2020-01-01-test.rs")],
+  );
+  assert_eq!(sources[std::path::Path::new("about.html")], [paths.pages.join("about.md")]);
+}
+
+#[test]
+fn links_locates_findings_behind_pretty_permalinks() {
+  let dirs = setup();
+  let paths = dirs.as_path_config();
+  fs::write(paths.posts.join(MD_FILENAME), "tags: game\n\n# Title\n\n[Gone](/gone.html)\n").unwrap();
+  fs::create_dir_all(paths.public_posts.join("2020/test")).unwrap();
+  fs::write(paths.public_posts.join("2020/test/index.html"), "<a href=\"/gone.html\">Gone</a>").unwrap();
+  let post = Metadata {
+    name: "2020-01-01-test".to_string(),
+    permalink: "/posts/2020/test/".to_string(),
+    ..Default::default()
+  };
+
+  let findings = check::links(&paths.public, &check::sources(&[post], &paths));
+
+  assert_eq!(findings[0].file, paths.posts.join(MD_FILENAME).to_str().unwrap());
+  assert_eq!(findings[0].line, 5);
+}
@@ -0,0 +1,109 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::common;
+use crate::refute;
+use website::collection::{self, Collection};
+use website::config::SiteConfig;
+use website::index::Sort;
+use website::post::Metadata;
+
+fn notes() -> Collection {
+  Collection {
+    name: "notes".to_string(),
+    title: "Notes".to_string(),
+    source: PathBuf::from("notes"),
+    permalink: "/notes/:slug.html".to_string(),
+    template: "collection".to_string(),
+    sort: Sort::Updated,
+    feed: true,
+  }
+}
+
+#[test]
+fn posts_are_the_default_collection() {
+  let site = SiteConfig { collections: vec![notes()], ..Default::default() };
+
+  let posts = site.posts();
+
+  assert_eq!(posts.name, collection::POSTS);
+  assert_eq!(posts.permalink, "/posts/:name.html");
+  assert_eq!(posts.template, "home");
+  assert_eq!(posts.url(), "/");
+  assert_eq!(posts.feed_url(), "/posts/feed.xml");
+}
+
+#[test]
+fn paths_give_other_collections_directories_of_their_own() {
+  let dirs = common::setup();
+  let paths = dirs.as_path_config();
+
+  let posts_paths = Collection::default().paths(&paths);
+  let notes_paths = notes().paths(&paths);
+
+  assert_eq!(posts_paths.artifacts, paths.artifacts);
+  assert_eq!(posts_paths.public_posts, paths.public_posts);
+  assert_eq!(notes_paths.posts, PathBuf::from("notes"));
+  assert_eq!(notes_paths.artifacts.parent(), paths.artifacts.parent());
+  assert!(notes_paths.artifacts.to_str().unwrap().ends_with("-notes"));
+  assert_eq!(notes_paths.public_posts, paths.public.join("notes"));
+  assert_eq!(notes().url(), "/notes/");
+}
+
+#[test]
+fn listing_has_cards_for_published_items_in_order_with_the_feed() {
+  let dirs = common::setup();
+  let paths = notes().paths(&dirs.as_path_config());
+  let item = |name: &str, updated: &str| Metadata {
+    name: name.to_string(),
+    title: name.to_string(),
+    created: name[..10].to_string(),
+    updated: updated.to_string(),
+    permalink: format!("/notes/{name}.html"),
+    ..Default::default()
+  };
+  let items = [
+    item("2020-02-01-newer", ""),
+    item("2020-01-01-older", "2020-03-01"),
+    Metadata { name: "draft-idea".to_string(), ..Default::default() },
+  ];
+
+  collection::listing(&items, &notes(), &paths, &SiteConfig::default());
+
+  let html = fs::read_to_string(paths.public_posts.join("index.html")).unwrap();
+  assert!(html.contains("<title>Notes - Electric Visions</title>"));
+  assert!(html.contains("href=\"/notes/feed.xml\""));
+  assert!(html.contains("<nav>"));
+  // Updated sort puts the older note that was updated since first
+  let older = html.find("/notes/2020-01-01-older.html").unwrap();
+  let newer = html.find("/notes/2020-02-01-newer.html").unwrap();
+  assert!(older < newer);
+  refute!(html.contains("draft-idea"));
+}
+
+#[test]
+fn place_reports_items_outside_the_collection_or_sharing_a_url() {
+  let dirs = common::setup();
+  let paths = notes().paths(&dirs.as_path_config());
+  let item = |name: &str, permalink: &str| Metadata {
+    name: name.to_string(),
+    permalink: permalink.to_string(),
+    ..Default::default()
+  };
+  let items = vec![
+    item("first", "/notes/same.html"),
+    item("second", "/notes/same.html"),
+    item("stray", "/stray.html"),
+    item("kept", "/notes/kept.html"),
+  ];
+
+  let (placed, findings) = collection::place(items, &notes(), &paths);
+
+  let names: Vec<&str> = placed.iter().map(|p| p.name.as_str()).collect();
+  assert_eq!(names, ["first", "kept"]);
+  assert_eq!(findings.len(), 2);
+  assert!(findings[0].file.ends_with("second.md"));
+  assert!(findings[0].message.contains("first"));
+  assert!(findings[1].file.ends_with("stray.md"));
+  assert!(findings[1].message.contains("must be under /notes/"));
+}
@@ -23,6 +23,7 @@ pub struct TempPathConfig {
   artifacts: TempDir,
   public: TempDir,
   public_posts: TempDir,
+  dist: TempDir,
 }
 
 impl TempPathConfig {
@@ -33,6 +34,7 @@ impl TempPathConfig {
       artifacts: self.artifacts.path().to_path_buf(),
       public: self.public.path().to_path_buf(),
       public_posts: self.public_posts.path().to_path_buf(),
+      dist: self.dist.path().to_path_buf(),
     }
   }
 }
@@ -44,6 +46,7 @@ pub fn setup() -> TempPathConfig {
   let artifacts = TempDir::with_prefix("artifacts").unwrap();
   let public = TempDir::with_prefix("public").unwrap();
   let public_posts = TempDir::with_prefix_in("public_posts", public.path()).unwrap();
+  let dist = TempDir::with_prefix("dist").unwrap();
 
   TempPathConfig {
     posts,
@@ -51,6 +54,7 @@ pub fn setup() -> TempPathConfig {
     artifacts,
     public,
     public_posts,
+    dist,
   }
 }
 
@@ -132,6 +136,7 @@ pub fn make_post(dirs: &post::PathConfig) -> post::Metadata {
     updated: String::new(),
     tags: "game".to_string(),
     intro: "Some intro text\n".to_string(),
+    ..Default::default()
   };
 
   create_page(&dirs.posts.join(MD_FILENAME), &post);
@@ -147,6 +152,7 @@ pub fn make_draft(dirs: &post::PathConfig) -> post::Metadata {
     updated: String::new(),
     tags: "game".to_string(),
     intro: "Some draft text\n".to_string(),
+    ..Default::default()
   };
 
   create_page(&dirs.posts.join("draft-test.md"), &post);
@@ -175,6 +181,7 @@ pub fn make_page(dirs: &post::PathConfig) -> &str {
     updated: "2020-01-02".to_string(),
     tags: "game".to_string(),
     intro: "Some stuff about me\n".to_string(),
+    ..Default::default()
   };
 
   create_page(&dirs.pages.join(format!("{filename}.md")), &post);
@@ -0,0 +1,77 @@
+use std::fs::File;
+use std::fs;
+use std::time::{Duration, SystemTime};
+use tempfile::TempDir;
+
+use crate::refute;
+use website::compress::{self, Encoding};
+
+#[test]
+fn precompress_writes_gzip_siblings_for_compressible_files() {
+  let root = TempDir::with_prefix("public").unwrap();
+  let path = root.path();
+  fs::write(path.join("index.html"), "<p>Hello</p>").unwrap();
+  fs::write(path.join("cube.webp"), "image").unwrap();
+
+  compress::precompress(path, &[Encoding::Gzip]);
+
+  let gz = fs::read(path.join("index.html.gz")).unwrap();
+  assert_eq!(gz[0..2], [0x1f, 0x8b]);
+  refute!(path.join("cube.webp.gz").exists());
+}
+
+#[test]
+fn precompress_skips_unchanged_files() {
+  let root = TempDir::with_prefix("public").unwrap();
+  let path = root.path();
+  fs::write(path.join("main.css"), "a{color:red}").unwrap();
+  compress::precompress(path, &[Encoding::Gzip]);
+  let past = SystemTime::now() - Duration::from_mins(1);
+  File::options()
+    .write(true)
+    .open(path.join("main.css.gz"))
+    .unwrap()
+    .set_modified(past)
+    .unwrap();
+  File::options()
+    .write(true)
+    .open(path.join("main.css"))
+    .unwrap()
+    .set_modified(past - Duration::from_mins(1))
+    .unwrap();
+
+  compress::precompress(path, &[Encoding::Gzip]);
+
+  let modified = fs::metadata(path.join("main.css.gz")).unwrap().modified().unwrap();
+  assert_eq!(modified, past);
+}
+
+#[test]
+fn precompress_removes_siblings_of_deleted_files() {
+  let root = TempDir::with_prefix("public").unwrap();
+  let path = root.path();
+  fs::write(path.join("old.html.gz"), "stale").unwrap();
+  fs::write(path.join("old.html.br"), "stale").unwrap();
+
+  compress::precompress(path, &[Encoding::Gzip]);
+
+  refute!(path.join("old.html.gz").exists());
+  refute!(path.join("old.html.br").exists());
+}
+
+#[test]
+fn remove_deletes_all_siblings() {
+  let root = TempDir::with_prefix("dist").unwrap();
+  let path = root.path();
+  fs::write(path.join("index.html"), "<p>Hello</p>").unwrap();
+  fs::write(path.join("index.html.gz"), "gz").unwrap();
+  fs::write(path.join("index.html.br"), "br").unwrap();
+  fs::write(path.join("archive.tar.gz"), "tar").unwrap();
+
+  compress::remove(path);
+
+  assert!(path.join("index.html").exists());
+  assert!(path.join("archive.tar.gz").exists());
+  refute!(path.join("index.html.gz").exists());
+  refute!(path.join("index.html.br").exists());
+}
@@ -0,0 +1,14 @@
+use website::date;
+use website::post;
+
+#[test]
+fn from_days_converts_days_since_the_epoch() {
+  assert_eq!(date::from_days(0), "1970-01-01");
+  assert_eq!(date::from_days(11_016), "2000-02-29");
+  assert_eq!(date::from_days(20_724), "2026-09-28");
+}
+
+#[test]
+fn today_is_a_date() {
+  assert!(post::is_date(&date::today()));
+}
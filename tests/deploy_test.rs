@@ -0,0 +1,102 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::TempDir;
+
+use crate::refute;
+use website::deploy::{Cloudflare, Deployer, GitBranch, Local, Rsync, S3, Target};
+
+fn stage() -> TempDir {
+  let dist = TempDir::with_prefix("dist").unwrap();
+  fs::create_dir(dist.path().join("posts")).unwrap();
+  fs::write(dist.path().join("index.html"), "home").unwrap();
+  fs::write(dist.path().join("posts/a.html"), "post").unwrap();
+  dist
+}
+
+fn args(command: &Command) -> Vec<String> {
+  command.get_args().map(|a| a.to_string_lossy().into_owned()).collect()
+}
+
+#[test]
+fn local_replaces_directory_with_staged_files() {
+  let dist = stage();
+  let target = TempDir::with_prefix("www").unwrap();
+  fs::write(target.path().join("old.html"), "old").unwrap();
+
+  let local = Local { dir: target.path().to_path_buf() };
+  local.deploy(dist.path()).unwrap();
+
+  assert_eq!(fs::read_to_string(target.path().join("posts/a.html")).unwrap(), "post");
+  refute!(target.path().join("old.html").exists());
+}
+
+#[test]
+fn local_fails_when_the_directory_cant_be_written() {
+  let dist = stage();
+  let target = TempDir::with_prefix("www").unwrap();
+  let file = target.path().join("file");
+  fs::write(&file, "not a directory").unwrap();
+
+  let local = Local { dir: file.join("www") };
+  let error = local.deploy(dist.path()).unwrap_err();
+
+  assert!(error.starts_with(&file.join("www").display().to_string()));
+}
+
+#[test]
+fn git_branch_force_pushes_staged_files_as_a_single_commit() {
+  let dist = stage();
+  let remote = TempDir::with_prefix("remote").unwrap();
+  Command::new("git").args(["init", "-q", "--bare"]).arg(remote.path()).status().unwrap();
+  let git_branch = GitBranch {
+    remote: remote.path().to_str().unwrap().to_string(),
+    branch: "gh-pages".to_string(),
+  };
+
+  git_branch.deploy(dist.path()).unwrap();
+  fs::write(dist.path().join("index.html"), "updated").unwrap();
+  git_branch.deploy(dist.path()).unwrap();
+
+  let git = |args: &[&str]| {
+    let output = Command::new("git").arg("-C").arg(remote.path()).args(args).output().unwrap();
+    String::from_utf8(output.stdout).unwrap()
+  };
+  assert_eq!(git(&["rev-list", "--count", "gh-pages"]), "1\n");
+  assert_eq!(git(&["show", "gh-pages:index.html"]), "updated");
+  assert_eq!(git(&["ls-tree", "-r", "--name-only", "gh-pages"]), "index.html\nposts/a.html\n");
+}
+
+#[test]
+fn deploy_fails_when_the_command_fails() {
+  let git_branch = GitBranch { remote: "/nonexistent/repo".to_string(), branch: "gh-pages".to_string() };
+
+  let result = git_branch.deploy(stage().path());
+
+  assert_eq!(result, Err("git failed with status: exit status: 128".to_string()));
+}
+
+#[test]
+fn commands_publish_the_staged_directory() {
+  let dir = Path::new("dist");
+
+  let wrangler = Cloudflare { project: "electricvisions".to_string() }.command(dir);
+  assert_eq!(wrangler.get_program(), "wrangler");
+  assert_eq!(args(&wrangler), ["pages", "deploy", "dist", "--project-name=electricvisions"]);
+
+  let minio = S3 { bucket: "site".to_string(), endpoint: "http://localhost:9000".to_string() };
+  assert_eq!(
+    args(&minio.command(dir)),
+    ["s3", "sync", "dist", "s3://site", "--delete", "--endpoint-url", "http://localhost:9000"],
+  );
+  let aws = S3 { bucket: "site".to_string(), ..Default::default() };
+  assert_eq!(args(&aws.command(dir)), ["s3", "sync", "dist", "s3://site", "--delete"]);
+
+  let rsync = Rsync { destination: "me@host:/var/www".to_string() }.command(dir);
+  assert_eq!(args(&rsync), ["-az", "--delete", "dist/", "me@host:/var/www"]);
+}
+
+#[test]
+fn target_defaults_to_cloudflare_pages() {
+  assert_eq!(Target::default().deployer().name(), "Cloudflare Pages (electricvisions)");
+}
@@ -0,0 +1,119 @@
+use std::fs;
+
+use crate::common;
+use crate::refute;
+use website::diagram;
+
+#[test]
+fn render_lays_out_dot_nodes_and_edges_top_to_bottom() {
+  let svg = diagram::render("dot", "digraph build {\n  md -> html [label=\"mmd\"]\n  rs -> md\n}\n").unwrap();
+
+  assert!(svg.starts_with("<svg class=\"diagram\""));
+  assert!(svg.contains("aria-label=\"build\""));
+  assert_eq!(svg.matches("<rect class=\"node\"").count(), 3);
+  assert_eq!(svg.matches("<line class=\"edge\"").count(), 2);
+  assert!(svg.contains(">mmd</text>"));
+  refute!(svg.contains("style="));
+
+  // rs and md both have a rank above html
+  let y = |label: &str| {
+    let text = &svg[..svg.find(&format!(">{label}</text>")).unwrap()];
+    let y = &text[text.rfind("y=\"").unwrap() + 3..];
+    y[..y.find('"').unwrap()].parse::<f64>().unwrap()
+  };
+  assert!(y("rs") < y("md"));
+  assert!(y("md") < y("html"));
+}
+
+#[test]
+fn render_honours_rankdir_shapes_and_labels() {
+  let svg =
+    diagram::render(
+      "dot",
+      "graph {\n  rankdir=LR\n  node [shape=ellipse]\n  a [label=\"Start <here>\"]\n  a -- b\n  c [shape=diamond]\n}\n",
+    ).unwrap();
+
+  assert_eq!(svg.matches("<ellipse class=\"node\"").count(), 2);
+  assert_eq!(svg.matches("<polygon class=\"node\"").count(), 1);
+  assert!(svg.contains(">Start &lt;here&gt;</text>"));
+  // Undirected edges have no arrowhead
+  refute!(svg.contains("marker-end"));
+}
+
+#[test]
+fn render_draws_sequence_diagrams() {
+  let svg = diagram::render("sequence", "participant Browser\nBrowser -> Server: GET /\nServer --> Browser: 200\n").unwrap();
+
+  assert!(svg.contains("aria-label=\"Sequence diagram\""));
+  assert_eq!(svg.matches("<line class=\"lifeline\"").count(), 2);
+  assert_eq!(svg.matches("<line class=\"edge\"").count(), 1);
+  assert_eq!(svg.matches("<line class=\"edge reply\"").count(), 1);
+  assert!(svg.contains(">GET /</text>"));
+}
+
+#[test]
+fn render_reports_errors_with_their_line() {
+  assert_eq!(diagram::render("dot", "digraph {\n  a -> \n}\n"), Err((2, "missing node after ->".to_string())));
+  assert_eq!(diagram::render("dot", "graph {\n  a -> b\n}\n"), Err((2, "-> in a graph".to_string())));
+  assert_eq!(diagram::render("dot", "digraph {\n  a -> b\n"), Err((2, "missing }".to_string())));
+  assert_eq!(
+    diagram::render("sequence", "A -> B: hi\nB says hello\n"),
+    Err((2, "expected A -> B: message, found \"B says hello\"".to_string())),
+  );
+}
+
+#[test]
+fn errors_reports_bad_diagrams_with_their_line() {
+  let dirs = common::setup();
+  let paths = dirs.as_path_config();
+  let file = paths.artifacts.join("2020-01-01-test.md");
+  fs::write(&file, "tags: test\n\n```dot\ndigraph { a -> b }\n```\n\n```sequence\nA -> B\nnonsense\n```\n").unwrap();
+
+  let findings = diagram::errors(&[file]);
+
+  assert_eq!(findings.len(), 1);
+  assert_eq!(findings[0].line, 9);
+  assert!(findings[0].message.starts_with("sequence diagram: expected A -> B"));
+}
+
+#[test]
+fn from_markdown_replaces_diagram_fences_and_caches_them() {
+  let dirs = common::setup();
+  let cache = dirs.as_path_config().artifacts.join("diagrams");
+  let markdown = "Intro\n\n```dot\ndigraph { a -> b }\n```\n\n```rust\nlet dot = 1;\n```\n";
+
+  let actual = diagram::from_markdown(markdown, &cache);
+
+  assert!(actual.starts_with("Intro\n\n<figure class=\"diagram\">\n<svg class=\"diagram\""));
+  assert!(actual.ends_with("</svg>\n</figure>\n\n```rust\nlet dot = 1;\n```\n"));
+  let cached: Vec<_> = fs::read_dir(&cache).unwrap().collect();
+  assert_eq!(cached.len(), 1);
+
+  // A cached SVG is used as is
+  let file = cached[0].as_ref().unwrap().path();
+  fs::write(&file, "<svg>cached</svg>").unwrap();
+  assert!(diagram::from_markdown(markdown, &cache).contains("<svg>cached</svg>"));
+}
+
+#[test]
+fn from_markdown_gives_repeated_diagrams_their_own_ids() {
+  let dirs = common::setup();
+  let cache = dirs.as_path_config().artifacts.join("diagrams");
+  let markdown = "```dot\ndigraph { a -> b }\n```\n\n```dot\ndigraph { a -> b }\n```\n";
+
+  let actual = diagram::from_markdown(markdown, &cache);
+
+  let ids: Vec<&str> = actual.split("<marker id=\"").skip(1).map(|s| &s[..s.find('"').unwrap()]).collect();
+  assert_eq!(ids.len(), 2);
+  assert_ne!(ids[0], ids[1]);
+  assert!(actual.contains(&format!("url(#{})", ids[1])));
+}
+
+#[test]
+fn from_markdown_leaves_diagrams_that_dont_parse_as_code() {
+  let dirs = common::setup();
+  let cache = dirs.as_path_config().artifacts.join("diagrams");
+  let markdown = "```dot\ndigraph {\n```\n";
+
+  assert_eq!(diagram::from_markdown(markdown, &cache), markdown);
+}
@@ -0,0 +1,81 @@
+use std::fs;
+
+use crate::common;
+use crate::refute;
+use website::collection::Collection;
+use website::config::SiteConfig;
+use website::feed;
+use website::post::Metadata;
+
+fn site() -> SiteConfig {
+  SiteConfig {
+    base_url: "https://example.com/".to_string(),
+    name: "Example".to_string(),
+    author: "Ann Author".to_string(),
+    ..Default::default()
+  }
+}
+
+fn posts() -> Vec<Metadata> {
+  vec![
+    Metadata { name: "draft-idea".to_string(), title: "Idea".to_string(), ..Default::default() },
+    Metadata {
+      name: "2020-02-01-second".to_string(),
+      title: "Second & last".to_string(),
+      created: "2020-02-01".to_string(),
+      updated: "2020-03-01".to_string(),
+      tags: "rust game".to_string(),
+      description: "The second post".to_string(),
+      ..Default::default()
+    },
+    Metadata {
+      name: "2020-01-01-first".to_string(),
+      title: "First".to_string(),
+      created: "2020-01-01".to_string(),
+      intro: "Where it all began\n".to_string(),
+      ..Default::default()
+    },
+  ]
+}
+
+#[test]
+fn atom_lists_published_posts_newest_first() {
+  let xml = feed::atom(&posts(), &Collection::default(), &site());
+
+  assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<feed xmlns=\"http://www.w3.org/2005/Atom\">"));
+  assert!(xml.contains("<title>Example - Posts</title>"));
+  assert!(xml.contains("<link href=\"https://example.com/posts/feed.xml\" rel=\"self\"/>"));
+  assert!(xml.contains("<id>https://example.com/</id>"));
+  assert!(xml.contains("<updated>2020-03-01T00:00:00Z</updated>\n  <author><name>Ann Author</name></author>"));
+  assert!(xml.contains(
+    "  <entry>\n    <title>Second &amp; last</title>\n\
+     \x20   <link href=\"https://example.com/posts/2020-02-01-second.html\"/>\n\
+     \x20   <id>https://example.com/posts/2020-02-01-second.html</id>\n\
+     \x20   <published>2020-02-01T00:00:00Z</published>\n\
+     \x20   <updated>2020-03-01T00:00:00Z</updated>\n\
+     \x20   <summary>The second post</summary>\n\
+     \x20   <category term=\"rust\"/>\n    <category term=\"game\"/>\n  </entry>"
+  ));
+  assert!(xml.contains("<summary>Where it all began</summary>"));
+  assert!(xml.find("Second").unwrap() < xml.find("First").unwrap());
+  refute!(xml.contains("Idea"));
+  assert!(xml.ends_with("</feed>\n"));
+}
+
+#[test]
+fn generate_writes_feed_xml_unless_the_collection_has_none() {
+  let dirs = common::setup();
+  let paths = dirs.as_path_config();
+
+  feed::generate(&posts(), &Collection { feed: false, ..Default::default() }, &paths, &site());
+  refute!(paths.public_posts.join("feed.xml").exists());
+  assert_eq!(feed::link(&Collection { feed: false, ..Default::default() }, &site()), "");
+
+  feed::generate(&posts(), &Collection::default(), &paths, &site());
+  let xml = fs::read_to_string(paths.public_posts.join("feed.xml")).unwrap();
+  assert!(xml.contains("<title>First</title>"));
+  assert_eq!(
+    feed::link(&Collection::default(), &site()),
+    "<link rel=\"alternate\" type=\"application/atom+xml\" title=\"Example - Posts\" href=\"/posts/feed.xml\">\n",
+  );
+}
@@ -0,0 +1,38 @@
+use website::figure::*;
+
+#[test]
+fn standalone_image_uses_title_as_caption() {
+  let markdown = "Intro\n\n![1 million cubes](/images/cube.webp \"Cube demo\")\n";
+
+  let expected = r#"Intro
+
+<figure>
+<img src="/images/cube.webp" alt="1 million cubes" />
+<figcaption>Cube demo</figcaption>
+</figure>
+"#;
+  assert_eq!(expected, from_markdown(markdown, false));
+}
+
+#[test]
+fn standalone_image_without_title_uses_alt_as_caption() {
+  let html = from_markdown("![1 million cubes](/images/cube.webp)", false);
+
+  assert!(html.contains("<figcaption>1 million cubes</figcaption>"));
+}
+
+#[test]
+fn figure_links_to_full_size_image() {
+  let html = from_markdown("![Cubes](/images/cube.webp)", true);
+
+  assert!(html.contains(
+    "<a href=\"/images/cube.webp\"><img src=\"/images/cube.webp\" alt=\"Cubes\" /></a>"
+  ));
+}
+
+#[test]
+fn image_within_a_paragraph_is_left_alone() {
+  let markdown = "Some text\n![Cubes](/images/cube.webp \"Cube demo\")\n";
+
+  assert_eq!(markdown, from_markdown(markdown, false));
+}
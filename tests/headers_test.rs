@@ -0,0 +1,80 @@
+use std::fs;
+use tempfile::TempDir;
+
+use crate::refute;
+use website::config::SiteConfig;
+use website::headers;
+use website::string::base64;
+
+fn site() -> SiteConfig {
+  SiteConfig {
+    cache_max_age: 600,
+    asset_cache_max_age: 31_536_000,
+    hsts: "max-age=31536000".to_string(),
+    referrer_policy: "no-referrer".to_string(),
+    ..Default::default()
+  }
+}
+
+#[test]
+fn base64_pads_to_a_multiple_of_four() {
+  assert_eq!(base64(b""), "");
+  assert_eq!(base64(b"f"), "Zg==");
+  assert_eq!(base64(b"fo"), "Zm8=");
+  assert_eq!(base64(b"foo"), "Zm9v");
+  assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+}
+
+#[test]
+fn generate_writes_security_headers_and_caches_fingerprinted_assets() {
+  let dir = TempDir::with_prefix("dist").unwrap();
+  fs::write(
+    dir.path().join("index.html"),
+    "<html><script src=\"/js/search.1234.js\"></script><script>hljs.highlightAll();</script></html>",
+  ).unwrap();
+
+  headers::generate(dir.path(), &site());
+
+  let headers = fs::read_to_string(dir.path().join("_headers")).unwrap();
+  assert!(headers.starts_with("/*\n  Content-Security-Policy: default-src 'self'; script-src 'self' 'sha256-"));
+  assert!(headers.contains("  Strict-Transport-Security: max-age=31536000\n"));
+  assert!(headers.contains("  Referrer-Policy: no-referrer\n"));
+  assert!(headers.contains("  Cache-Control: public, max-age=600\n"));
+  assert!(headers.contains("/css/*\n  ! Cache-Control\n  Cache-Control: public, max-age=31536000, immutable\n"));
+  assert!(headers.contains("/js/*\n"));
+}
+
+#[test]
+fn sources_hashes_inline_scripts_and_collects_external_origins() {
+  let dir = TempDir::with_prefix("dist").unwrap();
+  fs::create_dir(dir.path().join("posts")).unwrap();
+  fs::write(dir.path().join("posts/a.html"), r#"<html>
+<script type="application/ld+json">{"@type":"BlogPosting"}</script>
+<script>hljs.highlightAll();</script>
+<script src="https://cdn.example.com/lib.js"></script>
+<img src="//images.example.org/photo.jpg" alt="">
+<iframe src="https://www.youtube.com/embed/1"></iframe>
+</html>"#).unwrap();
+
+  let sources = headers::sources(dir.path());
+
+  assert_eq!(
+    sources.scripts.into_iter().collect::<Vec<_>>(),
+    ["'sha256-O6piNkhLv4BI/Oje+MccCmgUSrS1sIp+CMaOsvD/VWU='"],
+  );
+  assert!(sources.script_origins.contains("https://cdn.example.com"));
+  assert!(sources.image_origins.contains("https://images.example.org"));
+  assert!(sources.frame_origins.contains("https://www.youtube.com"));
+  refute!(sources.style_attributes);
+}
+
+#[test]
+fn policy_allows_inline_styles_only_when_style_attributes_are_used() {
+  let mut sources = headers::Sources::default();
+  sources.styles.insert("'sha256-abc='".to_string());
+  assert!(headers::policy(&sources).contains("style-src 'self' 'sha256-abc='; "));
+  refute!(headers::policy(&sources).contains("frame-src"));
+
+  sources.style_attributes = true;
+  assert!(headers::policy(&sources).contains("style-src 'self' 'unsafe-inline'; "));
+}
@@ -0,0 +1,26 @@
+use website::html;
+
+#[test]
+fn tags_returns_tags_with_attributes_and_lines() {
+  let source = "<p>\n<a href=\"/about.html\" class='x' hidden>About &amp; me</a>\n</p>";
+
+  let tags = html::tags(source);
+
+  assert_eq!(tags.len(), 4);
+  assert_eq!(tags[1].name, "a");
+  assert_eq!(tags[1].line, 2);
+  assert_eq!(tags[1].attribute("href"), Some("/about.html"));
+  assert_eq!(tags[1].attribute("class"), Some("x"));
+  assert_eq!(tags[1].attribute("hidden"), Some(""));
+  assert!(tags[2].closing);
+  assert_eq!(html::text_between(source, &tags[1], &tags[2]), "About & me");
+}
+
+#[test]
+fn tags_skips_comments_and_script_content() {
+  let source = "<!-- <a href=\"x\"> --><script>if (a <b) {}</script><img src=\"y\">";
+
+  let names: Vec<String> = html::tags(source).into_iter().map(|t| t.name).collect();
+
+  assert_eq!(names, ["script", "script", "img"]);
+}
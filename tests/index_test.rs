@@ -2,7 +2,9 @@ use std::fs;
 
 use crate::common::*;
 use crate::refute;
-use website::index;
+use website::config::SiteConfig;
+use website::index::{self, Sort};
+use website::post::Metadata;
 
 #[test]
 fn generates_index_page() {
@@ -11,7 +13,7 @@ fn generates_index_page() {
 
   make_page(&paths);
   let posts = [make_post(&paths), make_draft(&paths)];
-  index::generate(&posts, &paths);
+  index::generate(&posts, &paths, &SiteConfig::default());
   let html = fs::read_to_string(paths.public.join("index.html")).unwrap();
 
   assert!(html.contains("<nav>"));
@@ -27,6 +29,33 @@ fn generates_index_page() {
   assert!(html.contains("<a href=\"/posts/draft-test.html\">"));
 }
 
+#[test]
+fn generates_devlog_progress_on_the_first_page() {
+  let dirs = setup();
+  let paths = dirs.as_path_config();
+  make_page(&paths);
+  fs::write(
+    paths.pages.join("devlog.md"),
+    "title: Devlog\ncategory: devlog\n\n- [x] Learn Godot\n- [ ] Sound\n- [ ] Music\n",
+  ).unwrap();
+  let mut posts = vec![];
+  for day in (1..=3).rev() {
+    posts.push(Metadata { name: format!("2020-01-0{day}-post"), ..Default::default() });
+  }
+  let site = SiteConfig { posts_per_page: 2, ..Default::default() };
+
+  index::generate(&posts, &paths, &site);
+
+  let html = fs::read_to_string(paths.public.join("index.html")).unwrap();
+  assert!(html.contains(
+    "<p class=\"devlog\"><a href=\"/devlog.html\">Devlog</a> <span class=\"progress\">\
+     <progress value=\"1\" max=\"3\">1/3 done</progress> 1/3 done</span></p>"
+  ));
+  let page = fs::read_to_string(paths.public.join("page/2.html")).unwrap();
+  refute!(page.contains("devlog"));
+  refute!(page.contains("{devlog}"));
+}
+
 #[test]
 fn removes_draft_articles_from_index_page() {
   let dirs = setup();
@@ -43,3 +72,79 @@ fn removes_draft_articles_from_index_page() {
   assert!(html.contains("consectetur adipiscing elit sed do"));
   assert!(html.contains("Duis aute irure dolor in reprehenderit"));
 }
+
+#[test]
+fn generates_pages_with_prev_and_next_links() {
+  let dirs = setup();
+  let paths = dirs.as_path_config();
+  make_page(&paths);
+  let mut posts = vec![make_draft(&paths)];
+  for day in (1..=5).rev() {
+    posts.push(Metadata { name: format!("2020-01-0{day}-post"), ..Default::default() });
+  }
+  let site = SiteConfig { posts_per_page: 2, ..Default::default() };
+
+  index::generate(&posts, &paths, &site);
+
+  let first = fs::read_to_string(paths.public.join("index.html")).unwrap();
+  assert!(first.contains("<a href=\"/posts/draft-test.html\">"));
+  assert!(first.contains("<a href=\"/posts/2020-01-04-post.html\">"));
+  refute!(first.contains("2020-01-03-post"));
+  assert!(first.contains("<link rel=\"next\" href=\"/page/2.html\">"));
+  refute!(first.contains("rel=\"prev\""));
+
+  let second = fs::read_to_string(paths.public.join("page/2.html")).unwrap();
+  assert!(second.contains("<a href=\"/posts/2020-01-03-post.html\">"));
+  assert!(second.contains("<a rel=\"prev\" href=\"/\">"));
+  assert!(second.contains("<a rel=\"next\" href=\"/page/3.html\">"));
+  assert!(second.contains("Page 2 of 3"));
+  refute!(second.contains("draft-test"));
+
+  let last = fs::read_to_string(paths.public.join("page/3.html")).unwrap();
+  refute!(last.contains("rel=\"next\""));
+
+  index::generate(&posts, &paths, &SiteConfig::default());
+  refute!(paths.public.join("page").exists());
+}
+
+#[test]
+fn order_puts_pinned_posts_first_then_sorts() {
+  let post = |name: &str, updated: &str, pinned| Metadata {
+    name: name.to_string(),
+    created: name[..10].to_string(),
+    updated: updated.to_string(),
+    pinned,
+    ..Default::default()
+  };
+  let posts = [
+    post("2021-01-01-newest", "", false),
+    post("2020-01-01-revised", "2022-01-01", false),
+    post("2019-01-01-oldest", "", true),
+  ];
+  let names = |sort| -> Vec<String> {
+    index::order(posts.iter().collect(), sort).iter().map(|p| p.name.clone()).collect()
+  };
+
+  assert_eq!(names(Sort::Created), ["2019-01-01-oldest", "2021-01-01-newest", "2020-01-01-revised"]);
+  assert_eq!(names(Sort::Updated), ["2019-01-01-oldest", "2020-01-01-revised", "2021-01-01-newest"]);
+  assert!(index::card(&posts[2]).contains("<article class=\"card pinned\">"));
+}
+
+#[test]
+fn generate_marks_scheduled_posts_and_leaves_out_expired_ones() {
+  let dirs = setup();
+  let paths = dirs.as_path_config();
+  make_page(&paths);
+  let scheduled = "2020-01-02-scheduled".to_string();
+  let expired = "2020-01-01-expired".to_string();
+  let posts = [
+    Metadata { name: scheduled, publish: "2999-01-01".to_string(), ..Default::default() },
+    Metadata { name: expired, expires: "2020-02-01".to_string(), ..Default::default() },
+  ];
+
+  index::generate(&posts, &paths, &SiteConfig::default());
+
+  let html = fs::read_to_string(paths.public.join("index.html")).unwrap();
+  assert!(html.contains("<article class=\"card draft scheduled\">"));
+  refute!(html.contains("2020-01-01-expired"));
+}
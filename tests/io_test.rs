@@ -1,9 +1,12 @@
 use std::fs::File;
+use std::fs;
 use tempfile::TempDir;
 
 use crate::common;
 use crate::refute;
 use website::io;
+use website::post::{self, Metadata};
+use website::release::Manifest;
 
 #[test]
 fn load_template_reads_file_from_templates_directory() {
@@ -40,22 +43,74 @@ fn remove_stale_html_posts_removes_html_posts_that_no_longer_exist() {
   File::create(paths.public_posts.join("2018-04-08-language-and-framework.html")).unwrap();
   File::create(paths.public_posts.join("2018-04-15-javascript-physics-engines.html")).unwrap();
 
-  io::remove_stale_html_posts(&dirs.as_path_config());
+  io::remove_stale_html_posts(&post::build_all(&paths), &paths, &Manifest::new());
 
   assert!(paths.public_posts.join("2018-04-08-language-and-framework.html").exists());
   refute!(paths.public_posts.join("2018-04-15-javascript-physics-engines.html").exists());
 }
 
+#[test]
+fn remove_stale_html_posts_only_warns_about_deployed_posts() {
+  let dirs = common::setup();
+  let paths = dirs.as_path_config();
+  fs::create_dir_all(paths.public_posts.join("2099/scheduled")).unwrap();
+  fs::create_dir_all(paths.public_posts.join("2018/renamed")).unwrap();
+  File::create(paths.public_posts.join("2099/scheduled/index.html")).unwrap();
+  File::create(paths.public_posts.join("draft-idea.html")).unwrap();
+  File::create(paths.public_posts.join("2018/renamed/index.html")).unwrap();
+  let posts_dir = paths.public_posts.file_name().unwrap().to_str().unwrap();
+  let deployed: Manifest =
+    [(format!("{posts_dir}/2018/renamed/index.html"), ("hash".to_string(), 0))].into();
+
+  let gone = io::remove_stale_html_posts(&[], &paths, &deployed);
+
+  assert_eq!(gone, [format!("/{posts_dir}/2018/renamed/")]);
+  refute!(paths.public_posts.join("2099").exists());
+  refute!(paths.public_posts.join("draft-idea.html").exists());
+}
+
 #[test]
 fn drafts_are_removed_from_public_posts_directory() {
   let dirs = common::setup();
   let paths = dirs.as_path_config();
 
-  File::create(paths.public_posts.join("draft-language-and-framework.html")).unwrap();
-  File::create(paths.public_posts.join("2018-04-15-javascript-physics-engines.html")).unwrap();
+  let names = [
+    "draft-language-and-framework",
+    "2018-04-15-javascript-physics-engines",
+    "2099-01-01-scheduled",
+  ];
+  let posts: Vec<Metadata> = names.iter().map(|name| {
+    File::create(paths.public_posts.join(format!("{name}.html"))).unwrap();
+    let publish = if name.starts_with("2099") { "2099-01-01" } else { "" };
+    Metadata { name: name.to_string(), publish: publish.to_string(), ..Default::default() }
+  }).collect();
 
-  io::remove_drafts(&paths);
+  io::remove_drafts(&posts, &paths);
 
   refute!(paths.public_posts.join("draft-language-and-framework.html").exists());
   assert!(paths.public_posts.join("2018-04-15-javascript-physics-engines.html").exists());
+  refute!(paths.public_posts.join("2099-01-01-scheduled.html").exists());
+}
+
+#[test]
+fn remove_stale_html_posts_keeps_aliases_and_permalink_directories() {
+  let dirs = common::setup();
+  let paths = dirs.as_path_config();
+  let post = Metadata {
+    name: "2019-11-08-opengl-library".to_string(),
+    permalink: "/posts/2019/opengl-library/".to_string(),
+    aliases: "/posts/2019-11-08-opengl-library.html".to_string(),
+    ..Default::default()
+  };
+  fs::create_dir_all(paths.public_posts.join("2019/opengl-library")).unwrap();
+  fs::create_dir_all(paths.public_posts.join("2018/renamed")).unwrap();
+  File::create(paths.public_posts.join("2019/opengl-library/index.html")).unwrap();
+  File::create(paths.public_posts.join("2019-11-08-opengl-library.html")).unwrap();
+  File::create(paths.public_posts.join("2018/renamed/index.html")).unwrap();
+
+  io::remove_stale_html_posts(&[post], &paths, &Manifest::new());
+
+  assert!(paths.public_posts.join("2019/opengl-library/index.html").exists());
+  assert!(paths.public_posts.join("2019-11-08-opengl-library.html").exists());
+  refute!(paths.public_posts.join("2018").exists());
 }
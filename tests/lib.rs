@@ -1,8 +1,34 @@
 #![cfg(test)]
+mod a11y_test;
+mod archive_test;
+mod assets_test;
+mod callout_test;
+mod check_test;
+mod collection_test;
 mod common;
+mod compress_test;
+mod date_test;
+mod deploy_test;
+mod diagram_test;
+mod feed_test;
+mod figure_test;
+mod headers_test;
+mod html_test;
 mod index_test;
 mod io_test;
 mod markdown_test;
+mod math_test;
+mod minify_test;
 mod path_test;
+mod permalink_test;
 mod post_test;
+mod related_test;
+mod release_test;
 mod rs2md_test;
+mod scaffold_test;
+mod search_test;
+mod seo_test;
+mod series_test;
+mod sitemap_test;
+mod task_test;
+mod wiki_test;
@@ -1,7 +1,10 @@
 use std::fs;
+use std::time::Duration;
 
+use website::config::SiteConfig;
 use website::markdown::*;
 use crate::common;
+use crate::refute;
 
 #[test]
 fn to_html_posts_adds_html_posts_to_public_posts_directory() {
@@ -9,7 +12,7 @@ fn to_html_posts_adds_html_posts_to_public_posts_directory() {
   let paths = dirs.as_path_config();
 
   let posts = vec![common::make_artifact(&paths)];
-  to_html_posts(&posts, &paths);
+  to_html_posts(&posts, &paths, &SiteConfig::default());
 
   let expected = "<h1 id=\"atitle\">A Title</h1>\n\n<p>Some intro text</p>\n";
   let actual = fs::read_to_string(paths.public_posts.join("2020-01-01-test.html")).unwrap();
@@ -24,7 +27,7 @@ fn to_html_pages_adds_html_pages_to_public_directory() {
 
   let pages = vec![common::make_page(&paths)];
 
-  to_html_pages(pages, &paths);
+  to_html_pages(pages, &[], &paths, &SiteConfig::default());
 
   let actual = fs::read_to_string(paths.public.join("about.html")).unwrap();
   assert!(actual.starts_with("<!DOCTYPE html>"));
@@ -44,3 +47,71 @@ fn from_rs_or_md_to_md_converts_any_rs_files_and_copies_over_any_md_files() {
   assert!(paths.artifacts.join("2020-01-01-test.md").exists());
   assert!(paths.artifacts.join("2020-01-02-rust.md").exists());
 }
+
+#[test]
+fn preprocess_converts_standalone_images_to_figures() {
+  let markdown = "title: Test\n\n![Alt](/images/a.webp \"Caption\")\n";
+
+  let actual = preprocess(markdown, &[], &SiteConfig::default());
+
+  assert!(actual.starts_with("title: Test\n\n<figure>"));
+  assert!(actual.contains("<figcaption>Caption</figcaption>"));
+}
+
+#[test]
+fn preprocess_counts_tasks_across_the_whole_page() {
+  let markdown = "title: Test\n\n{progress}\n\n- [x] One\n\n```\n{progress}\n```\n\n- [ ] Two\n";
+
+  let actual = preprocess(markdown, &[], &SiteConfig::default());
+
+  assert!(actual.contains("<progress value=\"1\" max=\"2\">"));
+  assert!(actual.contains("```\n{progress}\n```"));
+  assert!(actual.ends_with("- <input type=\"checkbox\" class=\"task\" disabled> Two\n"));
+}
+
+#[test]
+fn preprocess_leaves_fenced_code_untouched() {
+  let markdown = "title: Test\n\n```\n![Alt](/images/a.webp)\n```\n";
+
+  let actual = preprocess(markdown, &[], &SiteConfig::default());
+
+  assert_eq!(markdown, actual);
+}
+
+#[test]
+fn lines_finds_tilde_indented_and_longer_fences() {
+  let markdown = "Text\n  ~~~~rust\n```\n~~~~\n````\n~~~\n````\n";
+
+  let actual: Vec<Line> = lines(markdown).into_iter().map(|(kind, _)| kind).collect();
+
+  let expected =
+    vec![Line::Prose, Line::Open, Line::Code, Line::Close, Line::Open, Line::Code, Line::Close];
+  assert_eq!(expected, actual);
+}
+
+#[test]
+fn mask_blanks_code_and_metadata_keeping_offsets() {
+  let markdown = "title: Test\n\nSee `[[a]]` and [[b]]\n```\n[[c]]\n```\n";
+
+  let actual = mask(markdown, true);
+
+  assert_eq!(markdown.len(), actual.len());
+  assert_eq!(markdown.find("[[b]]"), actual.find("[[b]]"));
+  refute!(actual.contains("title") || actual.contains("[[a]]") || actual.contains("[[c]]"));
+  assert_eq!(markdown.matches('\n').count(), actual.matches('\n').count());
+}
+
+#[test]
+fn links_changed_when_a_linked_post_changes_after_the_build() {
+  let dirs = common::setup();
+  let paths = dirs.as_path_config();
+  let posts = vec![common::make_artifact(&paths)];
+  let modified = fs::metadata(paths.artifacts.join(common::MD_FILENAME)).unwrap().modified().unwrap();
+  let before = modified - Duration::from_mins(1);
+  let after = modified + Duration::from_mins(1);
+
+  assert!(links_changed("See [[test]]\n", &posts, &paths, before));
+  refute!(links_changed("See [[test]]\n", &posts, &paths, after));
+  refute!(links_changed("No links\n", &posts, &paths, before));
+  refute!(links_changed("See [[another-post]]\n", &posts, &paths, before));
+}
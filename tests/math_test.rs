@@ -0,0 +1,77 @@
+use std::fs;
+
+use crate::common;
+use crate::refute;
+use website::math;
+
+fn row(tex: &str) -> String {
+  let mathml = math::to_mathml(tex, false).unwrap();
+  let start = "<math><semantics><mrow>".len();
+  mathml[start..mathml.find("<annotation").unwrap()].trim_end_matches("</mrow>").to_string()
+}
+
+#[test]
+fn to_mathml_converts_identifiers_numbers_operators_and_scripts() {
+  assert_eq!(
+    row("E = mc^2"),
+    "<mi>E</mi><mo>&#61;</mo><mi>m</mi><msup><mi>c</mi><mn>2</mn></msup>",
+  );
+  assert_eq!(
+    row("x_{i+1}^2 - \\alpha"),
+    "<msubsup><mi>x</mi><mrow><mi>i</mi><mo>&#43;</mo><mn>1</mn></mrow><mn>2</mn></msubsup>\
+     <mo>&#8722;</mo><mi>α</mi>",
+  );
+}
+
+#[test]
+fn to_mathml_converts_fractions_roots_functions_and_accents() {
+  assert_eq!(
+    row("\\frac{1}{2} \\sqrt{v} \\sqrt[3]{8}"),
+    "<mfrac><mrow><mn>1</mn></mrow><mrow><mn>2</mn></mrow></mfrac>\
+     <msqrt><mrow><mi>v</mi></mrow></msqrt><mroot><mrow><mn>8</mn></mrow><mrow><mn>3</mn></mrow></mroot>",
+  );
+  assert_eq!(
+    row("\\sin\\theta \\vec{F}"),
+    "<mi>sin</mi><mi>θ</mi><mover accent=\"true\"><mrow><mi>F</mi></mrow><mo>&#8594;</mo></mover>",
+  );
+}
+
+#[test]
+fn to_mathml_marks_display_math_and_keeps_the_source() {
+  let mathml = math::to_mathml("a < b", true).unwrap();
+
+  assert!(mathml.starts_with("<math display=\"block\"><semantics>"));
+  assert!(mathml.ends_with("<annotation encoding=\"application/x-tex\">a &#60; b</annotation></semantics></math>"));
+}
+
+#[test]
+fn to_mathml_reports_errors() {
+  assert_eq!(math::to_mathml("\\frac{1}", false), Err("missing argument for \\frac".to_string()));
+  assert_eq!(math::to_mathml("{x", false), Err("missing }".to_string()));
+  assert_eq!(math::to_mathml("\\foo", false), Err("unknown command \\foo".to_string()));
+}
+
+#[test]
+fn from_markdown_converts_math_outside_code_and_leaves_prices_alone() {
+  let prose = "Energy $E$ costs $5 or $10, `$x$` stays.\n\n$$\nF = ma\n$$\n";
+
+  let actual = math::from_markdown(prose);
+
+  assert!(actual.starts_with("Energy <math><semantics><mrow><mi>E</mi></mrow>"));
+  assert!(actual.contains("costs $5 or $10, `$x$` stays."));
+  assert!(actual.contains("\n\n<math display=\"block\">"));
+  refute!(actual.contains("$$"));
+}
+
+#[test]
+fn errors_reports_bad_math_with_its_line() {
+  let dirs = common::setup();
+  let paths = dirs.as_path_config();
+  let file = paths.artifacts.join("2020-01-01-test.md");
+  fs::write(&file, "tags: $x$\n\nFine $x^2$\n\n```\n$\\bad$\n```\nBroken $\\frac{1}$\n").unwrap();
+
+  let findings = math::errors(&[file]);
+
+  let messages: Vec<(usize, &str)> = findings.iter().map(|f| (f.line, f.message.as_str())).collect();
+  assert_eq!(messages, [(8, "math: missing argument for \\frac")]);
+}
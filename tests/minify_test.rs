@@ -0,0 +1,75 @@
+use std::fs;
+use tempfile::TempDir;
+
+use website::minify;
+
+#[test]
+fn html_collapses_whitespace_and_removes_comments() {
+  let html = r#"<!DOCTYPE html>
+<html lang="en">
+  <!-- comment -->
+  <body>
+    <p>
+      Some   <em>intro</em> text
+    </p>
+  </body>
+</html>
+"#;
+
+  assert_eq!(
+    minify::html(html),
+    r#"<!DOCTYPE html><html lang="en"><body><p>Some <em>intro</em> text</p></body></html>"#,
+  );
+}
+
+#[test]
+fn html_leaves_pre_and_script_content_alone() {
+  let pre = "<pre><code>fn main() {\n    println!(\"hi\");\n}\n</code></pre>";
+  let script = "<script>\n  const btn = 1;\n  // comment\n</script>";
+  let html = format!("<body>\n  {pre}\n  {script}\n</body>");
+
+  assert_eq!(minify::html(&html), format!("<body>{pre}{script}</body>"));
+}
+
+#[test]
+fn html_finds_the_end_of_raw_content_after_non_ascii_text() {
+  let script = "<SCRIPT>const s = \"İstanbul ẞ\";</SCRIPT>";
+  let html = format!("<body>\n  {script}\n  <p>Ünïcode  text</p>\n</body>");
+
+  assert_eq!(minify::html(&html), format!("<body>{script}<p>Ünïcode text</p></body>"));
+}
+
+#[test]
+fn css_removes_comments_and_whitespace() {
+  let css = r#"/* RESET */
+body {
+  font-family: "Atkinson Hyperlegible", sans-serif;
+  box-shadow: 0 2px 5px rgba(0, 0, 0, 0.5);
+}
+
+nav :hover > a {
+  &:hover {
+    color: var(--links-active);
+  }
+}
+"#;
+
+  assert_eq!(
+    minify::css(css),
+    r#"body{font-family:"Atkinson Hyperlegible",sans-serif;box-shadow:0 2px 5px rgba(0,0,0,0.5)}nav :hover>a{&:hover{color:var(--links-active)}}"#,
+  );
+}
+
+#[test]
+fn run_minifies_files_and_returns_sizes() {
+  let root = TempDir::with_prefix("dist").unwrap();
+  fs::write(root.path().join("index.html"), "<p>\n  Hello\n</p>\n").unwrap();
+  fs::write(root.path().join("main.css"), "a {\n  color: red;\n}\n").unwrap();
+  fs::write(root.path().join("app.js"), "let  a = 1;\n").unwrap();
+
+  let (before, after) = minify::run(root.path());
+
+  assert_eq!((before, after), (37, 24));
+  assert_eq!(fs::read_to_string(root.path().join("index.html")).unwrap(), "<p>Hello</p>");
+  assert_eq!(fs::read_to_string(root.path().join("app.js")).unwrap(), "let  a = 1;\n");
+}
@@ -0,0 +1,60 @@
+use std::fs;
+
+use crate::common::*;
+use website::permalink;
+use website::post::Metadata;
+
+fn post() -> Metadata {
+  Metadata {
+    name: "2019-11-08-opengl-library".to_string(),
+    title: "OpenGL Library Choice".to_string(),
+    created: "2019-11-08".to_string(),
+    ..Default::default()
+  }
+}
+
+#[test]
+fn expand_fills_in_the_pattern() {
+  let mut p = post();
+
+  assert_eq!(permalink::expand("", &p), "/posts/2019-11-08-opengl-library.html");
+  assert_eq!(permalink::expand("/posts/:year/:slug/", &p), "/posts/2019/opengl-library/");
+  assert_eq!(permalink::expand("/:year/:month/:day/:slug.html", &p), "/2019/11/08/opengl-library.html");
+
+  p.slug = "opengl".to_string();
+  assert_eq!(permalink::expand("/posts/:year/:slug/", &p), "/posts/2019/opengl/");
+
+  p.created = "draft".to_string();
+  assert_eq!(permalink::expand("/posts/:year/:slug/", &p), "/posts/2019-11-08-opengl-library.html");
+}
+
+#[test]
+fn apply_sets_url_and_output() {
+  let dirs = setup();
+  let paths = dirs.as_path_config();
+  let mut posts = [post()];
+
+  permalink::apply(&mut posts, "/posts/:year/:slug/");
+
+  assert_eq!(posts[0].url(), "/posts/2019/opengl-library/");
+  assert_eq!(posts[0].output(&paths), paths.public_posts.join("2019/opengl-library/index.html"));
+}
+
+#[test]
+fn redirects_writes_stubs_and_a_redirects_file() {
+  let dirs = setup();
+  let paths = dirs.as_path_config();
+  let mut posts = [post()];
+  posts[0].aliases = "/posts/2019-11-08-opengl-library.html /opengl/".to_string();
+  permalink::apply(&mut posts, "/posts/:year/:slug/");
+
+  permalink::redirects(&posts, &paths);
+
+  let stub = fs::read_to_string(paths.public_posts.join("2019-11-08-opengl-library.html")).unwrap();
+  assert!(stub.contains("<meta http-equiv=\"refresh\" content=\"0; url=/posts/2019/opengl-library/\">"));
+  assert!(paths.public.join("opengl/index.html").exists());
+  assert_eq!(
+    fs::read_to_string(paths.public.join("_redirects")).unwrap(),
+    "/posts/2019-11-08-opengl-library.html /posts/2019/opengl-library/ 301\n/opengl/ /posts/2019/opengl-library/ 301\n",
+  );
+}
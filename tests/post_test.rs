@@ -2,7 +2,9 @@ use std::fs::File;
 use std::fs;
 
 use crate::common::*;
-use website::post;
+use crate::refute;
+use website::config::SiteConfig;
+use website::post::{self, Metadata, Visibility};
 
 #[test]
 fn build_all_posts_metadata() {
@@ -23,7 +25,7 @@ fn post_process_adds_highlightjs_tags_created_and_updated_dates() {
   let posts = vec![make_post(&paths)];
   let html_path = make_html(&paths);
 
-  post::post_process(&posts, &paths);
+  post::post_process(&posts, &paths, &SiteConfig::default());
 
   let highlightjs = fs::read_to_string("templates/highlightjs.html").unwrap();
   let html = fs::read_to_string(html_path).unwrap();
@@ -39,11 +41,138 @@ fn post_process_again_does_not_add_highlightjs_tags() {
   let posts = vec![make_post(&paths)];
   let html_path = make_html(&paths);
 
-  post::post_process(&posts, &paths);
-  post::post_process(&posts, &paths);
+  post::post_process(&posts, &paths, &SiteConfig::default());
+  post::post_process(&posts, &paths, &SiteConfig::default());
 
   let highlightjs = fs::read_to_string("templates/highlightjs.html").unwrap();
   let html = fs::read_to_string(html_path).unwrap();
 
   assert_eq!(html.match_indices(&highlightjs).count(), 1);
 }
+
+#[test]
+fn build_uses_first_image_when_there_is_no_image_metadata() {
+  let dirs = setup();
+  let paths = dirs.as_path_config();
+  let path = paths.artifacts.join(MD_FILENAME);
+  fs::write(&path, "tags: game\n\n# Title\n\nIntro\n\n![Cubes](/images/cube.webp \"Demo\")\n").unwrap();
+
+  let post = post::build(&path);
+
+  assert_eq!(post.image, "/images/cube.webp");
+}
+
+#[test]
+fn post_process_adds_seo_metadata_to_head_once() {
+  let dirs = setup();
+  let paths = dirs.as_path_config();
+  let posts = vec![make_post(&paths)];
+  let html_path = make_html(&paths);
+
+  post::post_process(&posts, &paths, &SiteConfig::default());
+  post::post_process(&posts, &paths, &SiteConfig::default());
+
+  let html = fs::read_to_string(html_path).unwrap();
+  assert_eq!(html.matches("<!-- seo -->").count(), 1);
+  assert!(html.contains("<meta name=\"description\" content=\"Some intro text\">"));
+  assert!(html.contains("<!-- /seo -->\n</head>"));
+}
+
+#[test]
+fn post_process_adds_related_posts_before_the_end_of_body() {
+  let dirs = setup();
+  let paths = dirs.as_path_config();
+  let mut posts = vec![make_post(&paths), make_draft(&paths)];
+  posts[1].name = "2019-01-01-other".to_string();
+  let html_path = make_html(&paths);
+  fs::copy(&html_path, paths.public_posts.join("2019-01-01-other.html")).unwrap();
+  let site = SiteConfig { related_posts: 3, ..Default::default() };
+
+  post::post_process(&posts[..1], &paths, &site);
+  refute!(fs::read_to_string(&html_path).unwrap().contains("<!-- related -->"));
+
+  post::post_process(&posts, &paths, &site);
+  post::post_process(&posts, &paths, &site);
+
+  let html = fs::read_to_string(html_path).unwrap();
+  assert_eq!(html.matches("<!-- related -->").count(), 1);
+  assert!(html.contains("<a href=\"/posts/2019-01-01-other.html\">"));
+  assert!(html.contains("<!-- /related -->\n</body>"));
+}
+
+#[test]
+fn build_reads_series_metadata() {
+  let dirs = setup();
+  let paths = dirs.as_path_config();
+  let path = paths.artifacts.join(MD_FILENAME);
+  fs::write(&path, "tags: f#\nseries: F\\# Game Engine\nseries_order: 2\n\n# Title\n\nIntro\n").unwrap();
+
+  let post = post::build(&path);
+
+  assert_eq!(post.series, "F# Game Engine");
+  assert_eq!(post.series_order, Some(2));
+}
+
+#[test]
+fn post_process_keeps_series_older_newer_related_and_backlinks_sections_in_order() {
+  let dirs = setup();
+  let paths = dirs.as_path_config();
+  let mut posts = vec![make_draft(&paths), make_post(&paths)];
+  posts[0].name = "2020-02-01-next".to_string();
+  for p in &mut posts {
+    p.series = "Engine".to_string();
+  }
+  let html_path = make_html(&paths);
+  fs::copy(&html_path, paths.public_posts.join("2020-02-01-next.html")).unwrap();
+  fs::write(paths.artifacts.join("2020-02-01-next.md"), "See [[test]]\n").unwrap();
+  let site = SiteConfig { related_posts: 3, ..Default::default() };
+
+  post::post_process(&posts, &paths, &site);
+  let once = fs::read_to_string(&html_path).unwrap();
+  post::post_process(&posts, &paths, &site);
+  let html = fs::read_to_string(&html_path).unwrap();
+
+  assert_eq!(html, once);
+  let series = html.find("<!-- series -->").unwrap();
+  let chronology = html.find("<!-- chronology -->").unwrap();
+  let related = html.find("<!-- related -->").unwrap();
+  let backlinks = html.find("<!-- backlinks -->").unwrap();
+  assert!(series < chronology && chronology < related && related < backlinks);
+  assert!(html.contains("<a class=\"newer\" href=\"/posts/2020-02-01-next.html\">"));
+}
+
+#[test]
+fn visibility_depends_on_draft_prefix_publish_and_expires_dates() {
+  let post = |name: &str, publish: &str, expires: &str| Metadata {
+    name: name.to_string(),
+    publish: publish.to_string(),
+    expires: expires.to_string(),
+    ..Default::default()
+  };
+  let today = "2025-06-01";
+
+  assert_eq!(post("2025-01-01-post", "", "").visibility_on(today), Visibility::Published);
+  assert_eq!(post("draft-post", "", "").visibility_on(today), Visibility::Draft);
+  assert_eq!(post("2025-01-01-post", "2025-06-02", "").visibility_on(today), Visibility::Scheduled);
+  assert_eq!(post("2025-01-01-post", "2025-06-01", "").visibility_on(today), Visibility::Published);
+  assert_eq!(post("2025-01-01-post", "", "2025-06-01").visibility_on(today), Visibility::Expired);
+  assert!(Visibility::Scheduled.is_draft());
+  assert!(!Visibility::Expired.is_draft() && !Visibility::Expired.is_public());
+}
+
+#[test]
+fn post_process_adds_a_banner_to_scheduled_posts() {
+  let dirs = setup();
+  let paths = dirs.as_path_config();
+  let mut posts = vec![make_post(&paths)];
+  posts[0].publish = "2999-01-01".to_string();
+  let html_path = make_html(&paths);
+
+  post::post_process(&posts, &paths, &SiteConfig::default());
+  let html = fs::read_to_string(&html_path).unwrap();
+  assert!(html.contains("<p class=\"banner\">Scheduled for 2999-01-01"));
+
+  posts[0].publish = String::new();
+  post::post_process(&posts, &paths, &SiteConfig::default());
+  refute!(fs::read_to_string(&html_path).unwrap().contains("class=\"banner\""));
+}
@@ -0,0 +1,66 @@
+use crate::common::*;
+use website::post::Metadata;
+use website::related;
+
+fn post(name: &str, tags: &str) -> Metadata {
+  Metadata { name: name.to_string(), title: name.to_string(), tags: tags.to_string(), ..Default::default() }
+}
+
+#[test]
+fn find_ranks_posts_by_shared_tags_and_terms() {
+  let posts = vec![
+    post("2018-01-01-physics", "js physics"),
+    post("2018-02-01-engines", "js physics"),
+    post("2018-03-01-shaders", "js webgl"),
+    post("2018-04-01-keyboards", "hardware"),
+    post("draft-physics", "js physics"),
+  ];
+  let sources = [
+    "Rigid body physics engines in JavaScript",
+    "Comparing physics engines for rigid bodies",
+    "Writing shaders in JavaScript",
+    "Building a keyboard",
+    "Rigid body physics engines in JavaScript",
+  ].map(String::from);
+
+  let related = related::find(&posts, &sources, 2);
+
+  let names: Vec<&str> = related[0].iter().map(|p| p.name.as_str()).collect();
+  assert_eq!(names, ["2018-02-01-engines", "2018-03-01-shaders"]);
+  assert!(related[3].is_empty());
+}
+
+#[test]
+fn find_uses_related_metadata_instead() {
+  let mut posts = vec![post("2018-01-01-physics", "js"), post("2018-02-01-engines", "js")];
+  posts.push(post("2018-03-01-keyboards", "hardware"));
+  posts[0].related = "2018-03-01-keyboards missing".to_string();
+
+  let related = related::find(&posts, &[String::new(), String::new(), String::new()], 1);
+
+  assert_eq!(related[0], [&posts[2]]);
+}
+
+#[test]
+fn find_leaves_unpublished_posts_out_of_related_metadata() {
+  let mut posts = vec![post("2018-01-01-physics", "js"), post("draft-engines", "js")];
+  posts.push(post("2018-03-01-keyboards", "hardware"));
+  posts[2].expires = "2018-04-01".to_string();
+  posts[0].related = "draft-engines 2018-03-01-keyboards".to_string();
+
+  let related = related::find(&posts, &[String::new(), String::new(), String::new()], 1);
+
+  assert!(related[0].is_empty());
+}
+
+#[test]
+fn render_uses_the_card_partial() {
+  let dirs = setup();
+  let post = make_post(&dirs.as_path_config());
+
+  let html = related::render(&[&post]);
+
+  assert!(html.contains("<h2>Related posts</h2>"));
+  assert!(html.contains("<a href=\"/posts/2020-01-01-test.html\">"));
+  assert_eq!(related::render(&[]), "");
+}
@@ -0,0 +1,82 @@
+use std::fs;
+use tempfile::TempDir;
+
+use crate::common;
+use website::check::Sources;
+use website::post::Metadata;
+use website::release::{self, Change, Manifest};
+
+fn entry(path: &str, hash: &str, size: u64) -> (String, (String, u64)) {
+  (path.to_string(), (hash.to_string(), size))
+}
+
+#[test]
+fn manifest_round_trips_through_a_file() {
+  let dir = TempDir::with_prefix("dist").unwrap();
+  fs::create_dir(dir.path().join("posts")).unwrap();
+  fs::write(dir.path().join("posts/a.html"), "post").unwrap();
+
+  let manifest = release::manifest(dir.path());
+  let file = dir.path().join("deploy-manifest.tsv");
+  release::write(&file, &manifest);
+
+  assert_eq!(manifest["posts/a.html"].1, 4);
+  assert_eq!(release::read(&file), manifest);
+  assert!(release::read(&dir.path().join("missing.tsv")).is_empty());
+}
+
+#[test]
+fn diff_lists_added_changed_and_removed_files_in_path_order() {
+  let old: Manifest = [entry("index.html", "a", 10), entry("old.html", "b", 5), entry("same.css", "c", 7)].into();
+  let new: Manifest = [entry("index.html", "d", 12), entry("new.html", "e", 3), entry("same.css", "c", 7)].into();
+
+  let changes = release::diff(&old, &new);
+
+  assert_eq!(changes, [
+    Change::Changed("index.html".to_string(), 10, 12),
+    Change::Added("new.html".to_string(), 3),
+    Change::Removed("old.html".to_string(), 5),
+  ]);
+  assert_eq!(changes[0].to_string(), "~ index.html (10 -> 12 bytes)");
+}
+
+#[test]
+fn blockers_finds_drafts_placeholders_and_broken_links() {
+  let dirs = common::setup();
+  let paths = dirs.as_path_config();
+  let draft = Metadata { name: "draft-idea".to_string(), ..Default::default() };
+  let scheduled = Metadata {
+    name: "2099-01-01-later".to_string(),
+    publish: "2099-01-01".to_string(),
+    ..Default::default()
+  };
+  fs::create_dir(paths.dist.join("posts")).unwrap();
+  fs::write(paths.dist.join("posts/2099-01-01-later.html"), "<html></html>").unwrap();
+  fs::write(paths.dist.join("index.html"), r#"<html>
+<article class="card"><a href="/posts/draft-idea.html">Draft</a></article>
+<p>{intro}</p>
+<pre><code>format!("{name}")</code></pre>
+</html>"#).unwrap();
+
+  let messages: Vec<String> =
+    release::blockers(&paths.dist, &Sources::new(), &[draft, scheduled])
+    .into_iter()
+    .map(|f| f.message)
+    .collect();
+
+  assert_eq!(messages, [
+    "draft or scheduled post 2099-01-01-later in the output",
+    "link to draft or scheduled post draft-idea",
+    "unfilled placeholder {intro}",
+    "broken reference /posts/draft-idea.html",
+  ]);
+}
+
+#[test]
+fn blockers_accepts_clean_output() {
+  let dirs = common::setup();
+  let paths = dirs.as_path_config();
+  fs::write(paths.dist.join("index.html"), "<html><article class=\"card\">{}</article></html>").unwrap();
+
+  assert!(release::blockers(&paths.dist, &Sources::new(), &[]).is_empty());
+}
@@ -0,0 +1,75 @@
+use std::fs;
+
+use crate::common;
+use crate::refute;
+use website::markdown;
+use website::permalink;
+use website::rs2md;
+use website::scaffold;
+
+#[test]
+fn new_writes_a_markdown_draft_with_metadata_and_title() {
+  let dirs = common::setup();
+  let paths = dirs.as_path_config();
+
+  let path = scaffold::new(&paths.posts, "F# Game Loops", &["fsharp".to_string(), "games".to_string()], false).unwrap();
+
+  assert_eq!(path, paths.posts.join("draft-fsharp-game-loops.md"));
+  assert_eq!(fs::read_to_string(&path).unwrap(), "\
+mmd header: {{../templates/header.html}}
+mmd footer: {{../templates/footer.html}}
+css: /css/main.css
+tags: fsharp games
+
+
+# F# Game Loops
+
+");
+  assert!(scaffold::new(&paths.posts, "F# Game Loops", &[], false).is_err());
+}
+
+#[test]
+fn new_writes_an_rs_draft_that_rs2md_converts() {
+  let dirs = common::setup();
+  let paths = dirs.as_path_config();
+
+  let path = scaffold::new(&paths.posts, "Traits", &[], true).unwrap();
+
+  assert_eq!(path, paths.posts.join("draft-traits.rs"));
+  let contents = fs::read_to_string(&path).unwrap();
+  assert!(contents.starts_with("/**\nmmd header: "));
+  assert!(contents.ends_with("tags: untagged\n\n# Traits\n\n*/\n"));
+  let md = rs2md::from_rs(&contents);
+  assert!(markdown::is_metadata(md.lines().next().unwrap()));
+  assert!(md.contains("\n# Traits\n"));
+}
+
+#[test]
+fn publish_renames_the_draft_and_sets_created() {
+  let dirs = common::setup();
+  let paths = dirs.as_path_config();
+  fs::write(paths.posts.join("draft-typing.md"), "tags: keyboards\ncreated: draft\n\n# Typing\n").unwrap();
+  fs::write(paths.artifacts.join("draft-typing.md"), "").unwrap();
+
+  let path = scaffold::publish(&paths, "draft-typing.md", "2026-10-19", permalink::DEFAULT).unwrap();
+
+  assert_eq!(path, paths.posts.join("2026-10-19-typing.md"));
+  assert_eq!(
+    fs::read_to_string(&path).unwrap(),
+    "tags: keyboards\ncreated: 2026-10-19\naliases: /posts/draft-typing.html\n\n# Typing\n",
+  );
+  refute!(paths.posts.join("draft-typing.md").exists());
+  refute!(paths.artifacts.join("draft-typing.md").exists());
+}
+
+#[test]
+fn publish_only_adds_an_alias_when_the_url_changes() {
+  let dirs = common::setup();
+  let paths = dirs.as_path_config();
+  fs::write(paths.posts.join("draft-traits.rs"), "/**\ntags: rust\nslug: traits\n\n# Traits\n*/\n").unwrap();
+
+  let path = scaffold::publish(&paths, "traits", "2026-10-19", "/posts/:slug/").unwrap();
+
+  assert_eq!(fs::read_to_string(path).unwrap(), "/**\ntags: rust\nslug: traits\n\n# Traits\n*/\n");
+  assert!(scaffold::publish(&paths, "traits", "2026-10-19", "/posts/:slug/").is_err());
+}
@@ -0,0 +1,85 @@
+use std::fs;
+
+use crate::common::*;
+use crate::refute;
+use website::post::Metadata;
+use website::search;
+
+#[test]
+fn tokens_lowercases_and_keeps_language_names() {
+  assert_eq!(search::tokens("Learning F# and C++ in 2019!"), [
+    "learning", "f#", "and", "c++", "in", "2019",
+  ]);
+}
+
+#[test]
+fn stem_strips_common_suffixes() {
+  let stems: Vec<String> =
+    ["engines", "libraries", "classes", "running", "updated", "quickly", "physics", "rust"]
+    .iter()
+    .map(|w| search::stem(w))
+    .collect();
+  assert_eq!(stems, ["engine", "library", "class", "run", "updat", "quick", "physic", "rust"]);
+}
+
+#[test]
+fn terms_in_removes_stop_words() {
+  assert_eq!(search::terms_in("The physics of the engines"), ["physic", "engine"]);
+}
+
+#[test]
+fn text_skips_metadata_transclusions_and_every_kind_of_fence() {
+  let source = "title: Test\n\n{{nav.html}}\n# Heading\n\n````\n```\nnested\n````\n~~~rust\ntilde\n~~~\nProse\n";
+
+  let (headings, body) = search::text(source);
+
+  assert_eq!(headings, " Heading\n");
+  assert_eq!(body, "\nProse\n");
+}
+
+#[test]
+fn index_weights_titles_tags_headings_and_body() {
+  let post = Metadata {
+    name: "2020-01-01-test".to_string(),
+    title: "Physics Engines".to_string(),
+    tags: "js physics".to_string(),
+    created: "2020-01-01".to_string(),
+    ..Default::default()
+  };
+  let source = "tags: js physics\n\n# Physics Engines\n\n## Engines\n\nAn engine.\n\n```\nlet ignored = 1;\n```\n";
+
+  let json = search::index(&[&post], &[source.to_string()]);
+
+  assert!(json.contains(r#""docs":[{"url":"/posts/2020-01-01-test.html","title":"Physics Engines","created":"2020-01-01","tags":"js physics"}]"#));
+  assert!(json.contains(r#""engine":[0,17]"#)); // title 10 + 2 headings 3 each + body 1
+  assert!(json.contains(r#""physic":[0,19]"#)); // title 10 + tags 6 + heading 3
+  refute!(json.contains("ignor"));
+  assert!(json.starts_with(r#"{"stop":["a","about","#));
+}
+
+#[test]
+fn generate_leaves_drafts_out_unless_asked() {
+  let dirs = setup();
+  let paths = dirs.as_path_config();
+  let posts = vec![
+    Metadata { name: "2020-01-01-test".to_string(), title: "Published".to_string(), ..Default::default() },
+    Metadata { name: "draft-test".to_string(), title: "Unfinished".to_string(), ..Default::default() },
+    Metadata {
+      name: "2020-01-02-offer".to_string(),
+      title: "Expired".to_string(),
+      expires: "2020-02-01".to_string(),
+      ..Default::default()
+    },
+  ];
+
+  search::generate(&posts, &paths, false);
+  let json = fs::read_to_string(paths.public.join("search-index.json")).unwrap();
+  assert!(json.contains("\"Published\""));
+  refute!(json.contains("Unfinished"));
+  assert!(fs::read_to_string(paths.public.join("search.html")).unwrap().contains("<nav>"));
+
+  search::generate(&posts, &paths, true);
+  let json = fs::read_to_string(paths.public.join("search-index.json")).unwrap();
+  assert!(json.contains("\"Unfinished\""));
+  refute!(json.contains("Expired"));
+}
@@ -0,0 +1,73 @@
+use website::config::SiteConfig;
+use website::post::Metadata;
+use website::seo;
+
+fn site() -> SiteConfig {
+  SiteConfig {
+    base_url: "https://example.com".to_string(),
+    name: "Electric Visions".to_string(),
+    author: "Phil Thompson".to_string(),
+    ..Default::default()
+  }
+}
+
+fn post() -> Metadata {
+  Metadata {
+    name: "2019-11-08-opengl-library".to_string(),
+    title: "OpenGL Library Choice".to_string(),
+    created: "2019-11-08".to_string(),
+    tags: "game f# opengl".to_string(),
+    intro: "WebGL interfaces *directly* with [JavaScript](https://js.org).\n".to_string(),
+    image: "/images/cube-demo.webp".to_string(),
+    ..Default::default()
+  }
+}
+
+#[test]
+fn post_head_has_description_canonical_and_social_tags() {
+  let head = seo::post_head(&post(), &site());
+
+  assert!(head.contains(r#"<meta name="description" content="WebGL interfaces directly with JavaScript.">"#));
+  assert!(head.contains(r#"<link rel="canonical" href="https://example.com/posts/2019-11-08-opengl-library.html">"#));
+  assert!(head.contains(r#"<meta property="og:title" content="OpenGL Library Choice">"#));
+  assert!(head.contains(r#"<meta property="og:image" content="https://example.com/images/cube-demo.webp">"#));
+  assert!(head.contains(r#"<meta property="article:tag" content="f#">"#));
+  assert!(head.contains(r#"<meta name="twitter:card" content="summary_large_image">"#));
+}
+
+#[test]
+fn post_head_has_blog_posting_json_ld() {
+  let head = seo::post_head(&post(), &site());
+
+  assert!(head.contains(r#"<script type="application/ld+json">{"@context":"https://schema.org","@type":"BlogPosting","headline":"OpenGL Library Choice""#));
+  assert!(head.contains(r#""datePublished":"2019-11-08","dateModified":"2019-11-08""#));
+  assert!(head.contains(r#""keywords":"game, f#, opengl""#));
+  assert!(head.contains(r#""author":{"@type":"Person","name":"Phil Thompson"}"#));
+}
+
+#[test]
+fn post_head_without_image_uses_summary_card() {
+  let p = Metadata { image: String::new(), description: "Custom".to_string(), ..post() };
+
+  let head = seo::post_head(&p, &site());
+
+  assert!(head.contains(r#"<meta name="twitter:card" content="summary">"#));
+  assert!(head.contains(r#"<meta name="description" content="Custom">"#));
+  assert!(!head.contains("og:image"));
+}
+
+#[test]
+fn home_head_has_website_json_ld() {
+  let head = seo::home_head("I've been a professional software developer", &site());
+
+  assert!(head.contains(r#""@type":"WebSite","name":"Electric Visions","url":"https://example.com/""#));
+  assert!(head.contains(r#"<meta property="og:type" content="website">"#));
+}
+
+#[test]
+fn summarise_truncates_long_text_at_a_word_boundary() {
+  let summary = seo::summarise(&"word ".repeat(100));
+
+  assert!(summary.chars().count() <= 160);
+  assert!(summary.ends_with("word…"));
+}
@@ -0,0 +1,88 @@
+use std::fs;
+
+use crate::common::*;
+use crate::refute;
+use website::index;
+use website::post::Metadata;
+use website::series;
+use website::string::slugify;
+
+fn part(name: &str, series: &str, order: Option<usize>) -> Metadata {
+  Metadata {
+    name: name.to_string(),
+    title: format!("Title {name}"),
+    created: name[..10].to_string(),
+    series: series.to_string(),
+    series_order: order,
+    ..Default::default()
+  }
+}
+
+fn posts() -> Vec<Metadata> {
+  vec![
+    part("2019-11-20-library", "F# Game Engine", None),
+    part("2019-11-08-opengl", "F# Game Engine", None),
+    part("2019-11-06-reboot", "F# Game Engine", Some(1)),
+    part("2019-12-01-other", "", None),
+    part("draft-more-fsharp", "F# Game Engine", None),
+  ]
+}
+
+#[test]
+fn slugify_joins_lowercase_words_with_hyphens() {
+  assert_eq!(slugify("F# Game Engine"), "fsharp-game-engine");
+  assert_eq!(slugify("  Rust: a test-driven guide! "), "rust-a-test-driven-guide");
+}
+
+#[test]
+fn all_orders_parts_by_series_order_then_date_without_drafts() {
+  let posts = posts();
+
+  let all = series::all(&posts);
+
+  assert_eq!(all.len(), 1);
+  assert_eq!(all[0].url(), "/series/fsharp-game-engine.html");
+  let names: Vec<&str> = all[0].parts.iter().map(|p| p.name.as_str()).collect();
+  assert_eq!(names, ["2019-11-06-reboot", "2019-11-08-opengl", "2019-11-20-library"]);
+}
+
+#[test]
+fn number_sets_part_shown_on_cards() {
+  let mut posts = posts();
+
+  series::number(&mut posts);
+
+  let parts: Vec<usize> = posts.iter().map(|p| p.part).collect();
+  assert_eq!(parts, [3, 2, 1, 0, 0]);
+  assert!(index::card(&posts[1]).contains("<p class=\"part\">Part 2 of F# Game Engine</p>"));
+  refute!(index::card(&posts[3]).contains("class=\"part\""));
+}
+
+#[test]
+fn navigation_lists_parts_with_previous_and_next_links() {
+  let posts = posts();
+
+  let html = series::navigation(&posts[1], &posts);
+
+  assert!(html.contains("Part 2 of 3 in <a href=\"/series/fsharp-game-engine.html\">"));
+  assert!(html.contains("<li aria-current=\"page\">Title 2019-11-08-opengl</li>"));
+  assert!(html.contains("<a rel=\"prev\" href=\"/posts/2019-11-06-reboot.html\">"));
+  assert!(html.contains("<a rel=\"next\" href=\"/posts/2019-11-20-library.html\">"));
+  assert_eq!(series::navigation(&posts[3], &posts), "");
+}
+
+#[test]
+fn generate_writes_an_overview_page_per_series() {
+  let dirs = setup();
+  let paths = dirs.as_path_config();
+  let mut posts = posts();
+  series::number(&mut posts);
+
+  series::generate(&posts, &paths);
+
+  let html = fs::read_to_string(paths.public.join("series/fsharp-game-engine.html")).unwrap();
+  assert!(html.contains("<title>F# Game Engine - Electric Visions</title>"));
+  assert!(html.contains("<a href=\"/posts/2019-11-06-reboot.html\">"));
+  assert!(html.contains("Part 3 of F# Game Engine"));
+  refute!(html.contains("draft-more-fsharp"));
+}
@@ -0,0 +1,75 @@
+use std::fs;
+
+use crate::common::*;
+use crate::refute;
+use website::config::SiteConfig;
+use website::post::Metadata;
+use website::sitemap;
+
+fn site() -> SiteConfig {
+  SiteConfig {
+    base_url: "https://example.com/".to_string(),
+    ..Default::default()
+  }
+}
+
+#[test]
+fn generates_sitemap_without_drafts_or_noindex_pages() {
+  let dirs = setup();
+  let paths = dirs.as_path_config();
+  let mut post = make_post(&paths);
+  post.updated = "2021-03-04".to_string();
+  let posts = [post, make_draft(&paths)];
+  let pages = [
+    Metadata { name: "about".to_string(), created: "2018-04-02".to_string(), ..Default::default() },
+    Metadata { name: "404".to_string(), created: "404.md".to_string(), noindex: true, ..Default::default() },
+  ];
+
+  sitemap::generate(&posts, &pages, &paths, &site());
+
+  let xml = fs::read_to_string(paths.public.join("sitemap.xml")).unwrap();
+  assert!(xml.contains("<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">"));
+  assert!(xml.contains("<url><loc>https://example.com/</loc><lastmod>2021-03-04</lastmod></url>"));
+  assert!(xml.contains(
+    "<url><loc>https://example.com/posts/2020-01-01-test.html</loc><lastmod>2021-03-04</lastmod></url>"
+  ));
+  assert!(xml.contains("<url><loc>https://example.com/about.html</loc><lastmod>2018-04-02</lastmod></url>"));
+  refute!(xml.contains("draft-test"));
+  refute!(xml.contains("404"));
+}
+
+#[test]
+fn generates_robots_txt_disallowing_noindex_pages() {
+  let dirs = setup();
+  let paths = dirs.as_path_config();
+  let pages = [
+    Metadata { name: "404".to_string(), noindex: true, ..Default::default() },
+  ];
+
+  sitemap::generate(&[], &pages, &paths, &site());
+
+  let robots = fs::read_to_string(paths.public.join("robots.txt")).unwrap();
+  assert_eq!(
+    robots,
+    "User-agent: *\nDisallow: /404.html\n\nSitemap: https://example.com/sitemap.xml\n",
+  );
+}
+
+#[test]
+fn splits_into_sitemap_index_past_the_url_limit() {
+  let dirs = setup();
+  let paths = dirs.as_path_config();
+  let entries: Vec<sitemap::Entry> = (1..=5).map(|i| sitemap::Entry {
+    loc: format!("https://example.com/posts/{i}.html"),
+    lastmod: String::new(),
+  }).collect();
+
+  sitemap::write(&entries, &paths.public, "https://example.com", 2);
+
+  let index = fs::read_to_string(paths.public.join("sitemap.xml")).unwrap();
+  assert!(index.contains("<sitemapindex"));
+  assert!(index.contains("<sitemap><loc>https://example.com/sitemap-3.xml</loc></sitemap>"));
+  let last = fs::read_to_string(paths.public.join("sitemap-3.xml")).unwrap();
+  assert!(last.contains("<url><loc>https://example.com/posts/5.html</loc></url>"));
+  refute!(paths.public.join("sitemap-4.xml").exists());
+}
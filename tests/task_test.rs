@@ -0,0 +1,47 @@
+use website::task::{self, Progress};
+
+#[test]
+fn progress_counts_done_tasks_outside_code() {
+  let markdown = "- [x] Learn Godot\n- [ ] Integrate F#\n  * [X] Nested\n1. [ ] Numbered\n\n```\n- [x] Example\n```\n- [] Not a task\n";
+
+  assert_eq!(task::progress(markdown), Progress { done: 2, total: 4 });
+  assert_eq!(task::progress("No tasks\n"), Progress { done: 0, total: 0 });
+}
+
+#[test]
+fn progress_is_shown_as_a_count_and_bar() {
+  let progress = Progress { done: 3, total: 11 };
+
+  assert_eq!(progress.to_string(), "3/11 done");
+  assert_eq!(
+    progress.html(),
+    "<span class=\"progress\"><progress value=\"3\" max=\"11\">3/11 done</progress> 3/11 done</span>",
+  );
+}
+
+#[test]
+fn from_markdown_renders_tasks_as_disabled_checkboxes() {
+  let prose = "- [ ] Sound\n  - [x] Music\n- Plain [ ] item\n";
+
+  assert_eq!(
+    task::from_markdown(prose, Progress::default()),
+    "- <input type=\"checkbox\" class=\"task\" disabled> Sound\n  \
+     - <input type=\"checkbox\" class=\"task\" disabled checked> Music\n\
+     - Plain [ ] item\n",
+  );
+}
+
+#[test]
+fn from_markdown_fills_in_the_progress_directive() {
+  let actual = task::from_markdown("Progress: {progress}\n", Progress { done: 1, total: 2 });
+
+  assert_eq!(actual, format!("Progress: {}\n", Progress { done: 1, total: 2 }.html()));
+}
+
+#[test]
+fn from_markdown_leaves_the_progress_directive_in_inline_code() {
+  let progress = Progress { done: 1, total: 2 };
+  let actual = task::from_markdown("Write `{progress}` to get {progress}\n", progress);
+
+  assert_eq!(actual, format!("Write `{{progress}}` to get {}\n", progress.html()));
+}
@@ -0,0 +1,83 @@
+use std::fs;
+
+use crate::common;
+use website::post::Metadata;
+use website::wiki;
+
+fn posts() -> Vec<Metadata> {
+  let post = |name: &str, title: &str| Metadata {
+    name: name.to_string(),
+    title: title.to_string(),
+    ..Default::default()
+  };
+  vec![
+    post("2019-11-08-opengl-library", "OpenGL Library"),
+    post("2019-11-06-project-reboot-all-in-on-fsharp", "Project Reboot"),
+    post("draft-typing", "Typing"),
+  ]
+}
+
+#[test]
+fn from_markdown_links_to_posts_by_slug_or_name() {
+  let markdown = "See [[project-reboot-all-in-on-fsharp]] and [[2019-11-08-opengl-library|the library]].\n";
+
+  let actual = wiki::from_markdown(markdown, &posts());
+
+  assert_eq!(
+    actual,
+    "See [Project Reboot](/posts/2019-11-06-project-reboot-all-in-on-fsharp.html) and \
+     [the library](/posts/2019-11-08-opengl-library.html).\n",
+  );
+}
+
+#[test]
+fn from_markdown_leaves_inline_code_and_unknown_slugs_alone() {
+  let markdown = "`let a = [[opengl-library]];` and [[missing]]\n";
+
+  assert_eq!(wiki::from_markdown(markdown, &posts()), markdown);
+}
+
+#[test]
+fn from_markdown_leaves_links_to_drafts_alone() {
+  let markdown = "Coming soon: [[draft-typing]]\n";
+
+  assert_eq!(wiki::from_markdown(markdown, &posts()), markdown);
+}
+
+#[test]
+fn unresolved_reports_links_to_missing_posts_with_their_line() {
+  let dirs = common::setup();
+  let paths = dirs.as_path_config();
+  let file = paths.artifacts.join("2020-01-01-test.md");
+  fs::write(&file, "tags: game\n\n[[opengl-library]]\n\n```\n[[in-code]]\n```\n[[missing|Text]] [[draft-typing]]\n").unwrap();
+
+  let findings = wiki::unresolved(&posts(), &[file]);
+
+  let messages: Vec<(usize, &str)> = findings.iter().map(|f| (f.line, f.message.as_str())).collect();
+  assert_eq!(messages, [
+    (8, "unresolved link [[missing]]"),
+    (8, "link [[draft-typing]] to unpublished post draft-typing"),
+  ]);
+}
+
+#[test]
+fn backlinks_lists_published_posts_linking_to_each_post() {
+  let sources = [
+    "[[project-reboot-all-in-on-fsharp]]".to_string(),
+    "[[opengl-library]] and [[opengl-library]]".to_string(),
+    "[[opengl-library]]".to_string(),
+  ];
+  let posts = posts();
+
+  let backlinks = wiki::backlinks(&posts, &sources);
+
+  let names: Vec<Vec<&str>> =
+    backlinks.iter().map(|ps| ps.iter().map(|p| p.name.as_str()).collect()).collect();
+  assert_eq!(names, [
+    vec!["2019-11-06-project-reboot-all-in-on-fsharp"],
+    vec!["2019-11-08-opengl-library"],
+    vec![],
+  ]);
+  assert!(wiki::render(&backlinks[0]).contains("<h2>Referenced by</h2>"));
+  assert_eq!(wiki::render(&backlinks[2]), "");
+}